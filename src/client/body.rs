@@ -0,0 +1,209 @@
+#![deny(missing_docs)]
+
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use bytes::Bytes;
+use tokio::codec::{BytesCodec, FramedRead};
+use tokio::prelude::*;
+
+use super::HeaderMap;
+use proto::h1::RequestFraming;
+
+// Pieces of a streamed body, in order.
+pub(super) type BodySource = Box<dyn Stream<Item = Bytes, Error = io::Error> + Send>;
+
+// Trailer fields, ready once the last piece has been sent.
+pub(super) type TrailerSource = Box<dyn Future<Item = HeaderMap, Error = io::Error> + Send>;
+
+/// A request body, set with `RequestBuilder::body`.
+///
+/// Bodies held in memory are sent with a `Content-Length`. Readers and
+/// streams are sent as they are read, with `Transfer-Encoding: chunked`
+/// unless their length is known up front, as it is for `Body::from_file`.
+pub struct Body {
+    pub(super) kind: BodyKind,
+}
+
+pub(super) enum BodyKind {
+    Bytes(Bytes),
+    Streamed(Streamed),
+}
+
+pub(super) struct Streamed {
+    pub(super) source: BodySource,
+    // Sent as the `Content-Length`; chunked if unknown.
+    pub(super) length: Option<u64>,
+    // Only chunked bodies have trailers.
+    pub(super) trailers: Option<TrailerSource>,
+}
+
+impl Body {
+    /// Creates a body that is read from `reader` while the request is sent.
+    pub fn from_reader<R: AsyncRead + Send + 'static>(reader: R) -> Self {
+        Body::streamed(
+            FramedRead::new(reader, BytesCodec::new()).map(|chunk| chunk.freeze()),
+            None,
+        )
+    }
+
+    /// Creates a body from the pieces yielded by `stream`, sent as they
+    /// arrive.
+    pub fn from_stream<S>(stream: S) -> Self
+    where
+        S: Stream<Item = Bytes, Error = io::Error> + Send + 'static,
+    {
+        Body::streamed(stream, None)
+    }
+
+    /// Creates a body from the pieces yielded by `stream`, which add up to
+    /// `length` bytes, sent as the `Content-Length`.
+    ///
+    /// Sending fails if the stream ends early, and stops after `length`
+    /// bytes if it yields more.
+    pub fn from_sized_stream<S>(stream: S, length: u64) -> Self
+    where
+        S: Stream<Item = Bytes, Error = io::Error> + Send + 'static,
+    {
+        Body::streamed(stream, Some(length))
+    }
+
+    /// Opens the file at `path` to be read while the request is sent.
+    ///
+    /// The file's current size is sent as the `Content-Length`; sending fails
+    /// if it has shrunk by the time it is read, and stops at that size if it
+    /// has grown.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Body> {
+        let file = File::open(path)?;
+        let length = file.metadata()?.len();
+        Ok(Body::streamed(
+            FramedRead::new(BlockingFile(file), BytesCodec::new()).map(|chunk| chunk.freeze()),
+            Some(length),
+        ))
+    }
+
+    /// Sends the header fields `trailers` resolves to after the body, such as
+    /// a checksum computed by the code producing it.
+    ///
+    /// Trailers need `Transfer-Encoding: chunked`, so the body is sent
+    /// chunked even if its length is known. The future is polled once the
+    /// last piece has been sent. `Content-Length`, `Transfer-Encoding`,
+    /// `Trailer` and `Host` are rejected when sending, as are invalid names
+    /// and values. Servers ignore trailers they were not told to expect, so
+    /// name them in a `Trailer` request header.
+    pub fn with_trailers<F>(self, trailers: F) -> Self
+    where
+        F: Future<Item = HeaderMap, Error = io::Error> + Send + 'static,
+    {
+        let source: BodySource = match self.kind {
+            BodyKind::Bytes(bytes) => Box::new(stream::once(Ok(bytes))),
+            BodyKind::Streamed(streamed) => streamed.source,
+        };
+        Body {
+            kind: BodyKind::Streamed(Streamed {
+                source,
+                length: None,
+                trailers: Some(Box::new(trailers)),
+            }),
+        }
+    }
+
+    pub(super) fn framing(&self) -> RequestFraming {
+        match self.kind {
+            BodyKind::Bytes(ref bytes) => RequestFraming::Length(bytes.len() as u64),
+            BodyKind::Streamed(Streamed {
+                length: Some(length),
+                ..
+            }) => RequestFraming::Length(length),
+            BodyKind::Streamed(Streamed { length: None, .. }) => RequestFraming::Chunked,
+        }
+    }
+
+    // A copy to send again after a redirect, unless the body is consumed by
+    // sending it.
+    pub(super) fn try_clone(&self) -> Option<Body> {
+        match self.kind {
+            BodyKind::Bytes(ref bytes) => Some(Body::from(bytes.clone())),
+            BodyKind::Streamed(_) => None,
+        }
+    }
+
+    fn streamed<S>(stream: S, length: Option<u64>) -> Self
+    where
+        S: Stream<Item = Bytes, Error = io::Error> + Send + 'static,
+    {
+        Body {
+            kind: BodyKind::Streamed(Streamed {
+                source: Box::new(stream),
+                length,
+                trailers: None,
+            }),
+        }
+    }
+}
+
+impl fmt::Debug for Body {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            BodyKind::Bytes(ref bytes) => f.debug_tuple("Body").field(&bytes.len()).finish(),
+            BodyKind::Streamed(ref streamed) => f
+                .debug_struct("Body")
+                .field("streamed", &true)
+                .field("length", &streamed.length)
+                .field("trailers", &streamed.trailers.is_some())
+                .finish(),
+        }
+    }
+}
+
+impl From<Bytes> for Body {
+    fn from(bytes: Bytes) -> Self {
+        Body {
+            kind: BodyKind::Bytes(bytes),
+        }
+    }
+}
+
+impl From<Vec<u8>> for Body {
+    fn from(bytes: Vec<u8>) -> Self {
+        Body::from(Bytes::from(bytes))
+    }
+}
+
+impl From<String> for Body {
+    fn from(text: String) -> Self {
+        Body::from(text.into_bytes())
+    }
+}
+
+impl<'a> From<&'a str> for Body {
+    fn from(text: &'a str) -> Self {
+        Body::from(Bytes::from(text.as_bytes()))
+    }
+}
+
+impl<'a> From<&'a [u8]> for Body {
+    fn from(bytes: &'a [u8]) -> Self {
+        Body::from(Bytes::from(bytes))
+    }
+}
+
+impl<'a, const N: usize> From<&'a [u8; N]> for Body {
+    fn from(bytes: &'a [u8; N]) -> Self {
+        Body::from(&bytes[..])
+    }
+}
+
+// Files never report `WouldBlock`, so reading one simply blocks the thread
+// sending the request, which is blocked on the request anyway.
+struct BlockingFile(File);
+
+impl io::Read for BlockingFile {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buffer)
+    }
+}
+
+impl AsyncRead for BlockingFile {}
@@ -0,0 +1,65 @@
+#![deny(missing_docs)]
+
+use std::borrow::Cow;
+
+use encoding_rs::{Encoding, UTF_8};
+
+// Decodes `body` in the charset named by `content_type`, falling back to
+// UTF-8 when there is none or the label is unknown.
+pub(crate) fn decode<'a>(body: &'a [u8], content_type: Option<&str>) -> Cow<'a, str> {
+    let label = content_type.and_then(charset_param).unwrap_or("utf-8");
+    decode_with_label(body, label)
+}
+
+// Decodes `body` as `label`, a WHATWG encoding label such as `latin1` or
+// `shift_jis`. A byte order mark takes precedence, as in browsers.
+pub(super) fn decode_with_label<'a>(body: &'a [u8], label: &str) -> Cow<'a, str> {
+    let encoding = Encoding::for_label(label.trim().as_bytes()).unwrap_or(UTF_8);
+    encoding.decode(body).0
+}
+
+fn charset_param(content_type: &str) -> Option<&str> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        if !name.trim().eq_ignore_ascii_case("charset") {
+            return None;
+        }
+        let value = value.trim().trim_matches('"').trim();
+        if value.is_empty() {
+            None
+        } else {
+            Some(value)
+        }
+    })
+}
+
+#[test]
+fn charset_from_content_type() {
+    assert_eq!(None, charset_param("text/html"));
+    assert_eq!(Some("utf-8"), charset_param("text/html; charset=utf-8"));
+    assert_eq!(
+        Some("Shift_JIS"),
+        charset_param("text/html;Charset=\"Shift_JIS\";q=1")
+    );
+    assert_eq!(None, charset_param("text/html; charset="));
+    assert_eq!(None, charset_param("text/html; x-charset=latin1"));
+}
+
+#[test]
+fn decode_legacy_charsets() {
+    assert_eq!(
+        "café",
+        decode(b"caf\xe9", Some("text/plain; charset=ISO-8859-1"))
+    );
+    assert_eq!(
+        "日本",
+        decode(b"\x93\xfa\x96\x7b", Some("text/html; charset=shift_jis"))
+    );
+    assert_eq!("caf\u{fffd}", decode(b"caf\xe9", Some("text/plain")));
+    assert_eq!(
+        "caf\u{fffd}",
+        decode(b"caf\xe9", Some("text/plain; charset=bogus"))
+    );
+    assert_eq!("café", decode_with_label(b"caf\xc3\xa9", " UTF-8 "));
+    assert_eq!("é", decode_with_label(b"\xef\xbb\xbf\xc3\xa9", "latin1"));
+}
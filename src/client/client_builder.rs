@@ -0,0 +1,331 @@
+#![deny(missing_docs)]
+
+use std::cmp;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::language;
+use super::request_builder::validate_header;
+#[cfg(any(feature = "https", feature = "native-tls"))]
+use super::tls::{self, Certificate, Identity, TlsConnector, TlsSettings, TlsVersion};
+use super::{
+    HeaderMap, HttpResponseError, ParseMode, RedirectHook, RedirectPolicy, RequestProfile,
+    SimpleClient,
+};
+
+const DEFAULT_READ_BUFFER_SIZE: usize = 8 * 1024;
+
+// Settings shared by every clone of a client.
+#[derive(Debug)]
+pub(super) struct ClientConfig {
+    pub(super) default_headers: HeaderMap,
+    pub(super) timeout: Option<Duration>,
+    pub(super) deadline: Option<Duration>,
+    pub(super) redirect: RedirectPolicy,
+    pub(super) redirect_hook: Option<Arc<dyn RedirectHook>>,
+    pub(super) read_buffer_size: usize,
+    pub(super) parse_mode: ParseMode,
+    pub(super) preserve_header_order: bool,
+    pub(super) profile: Option<Arc<dyn RequestProfile>>,
+    // `None` uses the shared connector with the default settings, which is
+    // only created by the first `https` request that needs it.
+    #[cfg(any(feature = "https", feature = "native-tls"))]
+    pub(super) tls: Option<Arc<dyn TlsConnector>>,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        ClientConfig {
+            default_headers: HeaderMap::new(),
+            timeout: None,
+            deadline: None,
+            redirect: RedirectPolicy::default(),
+            redirect_hook: None,
+            read_buffer_size: DEFAULT_READ_BUFFER_SIZE,
+            parse_mode: ParseMode::default(),
+            preserve_header_order: false,
+            profile: None,
+            #[cfg(any(feature = "https", feature = "native-tls"))]
+            tls: None,
+        }
+    }
+}
+
+/// Configuration for a `SimpleClient`, created by `SimpleClient::builder`.
+///
+/// Errors in the configuration are deferred until `build`.
+#[derive(Default)]
+pub struct ClientBuilder {
+    config: ClientConfig,
+    #[cfg(any(feature = "https", feature = "native-tls"))]
+    tls: TlsSettings,
+    error: Option<HttpResponseError>,
+}
+
+impl ClientBuilder {
+    /// Creates a builder with the default configuration.
+    pub fn new() -> Self {
+        ClientBuilder::default()
+    }
+
+    /// Adds a header sent with every request.
+    ///
+    /// A header of the same name set on a request replaces all defaults for
+    /// that name.
+    pub fn default_header<K: Into<String>, V: Into<String>>(mut self, name: K, value: V) -> Self {
+        match validate_header(name.into(), value.into()) {
+            Ok(header) => self
+                .config
+                .default_headers
+                .append(header.name, header.content),
+            Err(err) => {
+                self.error.get_or_insert(err);
+            }
+        }
+        self
+    }
+
+    /// Sets the `User-Agent` sent with every request. None is sent by default.
+    pub fn user_agent<V: Into<String>>(mut self, user_agent: V) -> Self {
+        match validate_header("User-Agent".to_string(), user_agent.into()) {
+            Ok(header) => self
+                .config
+                .default_headers
+                .insert(header.name, header.content),
+            Err(err) => {
+                self.error.get_or_insert(err);
+            }
+        }
+        self
+    }
+
+    /// Sets the `Accept-Language` sent with every request from `locales`,
+    /// most preferred first. See `RequestBuilder::accept_language`.
+    pub fn accept_language<L: AsRef<str>>(mut self, locales: &[L]) -> Self {
+        match language::accept_language(locales) {
+            Ok(value) => self.config.default_headers.insert("Accept-Language", value),
+            Err(err) => {
+                self.error.get_or_insert(err);
+            }
+        }
+        self
+    }
+
+    /// Sets the default for `RequestBuilder::timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the default for `RequestBuilder::deadline`.
+    ///
+    /// The budget covers every redirect that is followed.
+    pub fn deadline(mut self, budget: Duration) -> Self {
+        self.config.deadline = Some(budget);
+        self
+    }
+
+    /// Sets whether redirects are followed. By default they are not.
+    pub fn redirect(mut self, policy: RedirectPolicy) -> Self {
+        self.config.redirect = policy;
+        self
+    }
+
+    /// Sets a hook that decides about each redirect the policy set with
+    /// `redirect` would follow. None is set by default.
+    pub fn redirect_hook<H: RedirectHook + 'static>(mut self, hook: H) -> Self {
+        self.config.redirect_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Sets the size of the buffer responses are read through, 8 KiB by
+    /// default.
+    pub fn read_buffer_size(mut self, size: usize) -> Self {
+        self.config.read_buffer_size = cmp::max(1, size);
+        self
+    }
+
+    /// Sets how strictly responses are parsed. Lenient by default.
+    pub fn parse_mode(mut self, mode: ParseMode) -> Self {
+        self.config.parse_mode = mode;
+        self
+    }
+
+    /// Sets whether the headers the client adds itself go after the ones set
+    /// on a request or as defaults, so those keep their positions. Off by
+    /// default.
+    ///
+    /// Either way headers are sent in the order they were added, defaults
+    /// first, and names are never recased. What this changes is that the
+    /// `Host` and `Connection` headers filled in by the client follow every
+    /// other header instead of leading, and that a `Content-Length` or
+    /// `Transfer-Encoding` header set on a request keeps its position with
+    /// only its value replaced, rather than being replaced by one written
+    /// last. A profile may still reorder everything. Response headers always
+    /// keep the order and spelling they were received in.
+    pub fn preserve_header_order(mut self, preserve: bool) -> Self {
+        self.config.preserve_header_order = preserve;
+        self
+    }
+
+    /// Sets a profile that shapes the headers of every request, such as
+    /// `BrowserProfile::Chrome`. None is used by default.
+    pub fn profile<P: RequestProfile + 'static>(mut self, profile: P) -> Self {
+        self.config.profile = Some(Arc::new(profile));
+        self
+    }
+
+    /// Trusts `certificate` as a root for `https` servers, in addition to
+    /// the default roots, e.g. for services signed by a private CA.
+    ///
+    /// An unusable certificate is reported by `build`.
+    #[cfg(any(feature = "https", feature = "native-tls"))]
+    pub fn add_root_certificate(mut self, certificate: Certificate) -> Self {
+        self.tls.roots.push(certificate);
+        self
+    }
+
+    /// Presents `identity` to servers that ask for a client certificate, as
+    /// for mutual TLS.
+    ///
+    /// A key the TLS backend cannot use is reported by `build`.
+    #[cfg(any(feature = "https", feature = "native-tls"))]
+    pub fn identity(mut self, identity: Identity) -> Self {
+        self.tls.identity = Some(identity);
+        self
+    }
+
+    /// Pins the public key `host` must present: `pin` is the base64 SHA-256
+    /// hash of the `SubjectPublicKeyInfo` of its leaf certificate, with or
+    /// without a `sha256/` prefix.
+    ///
+    /// Pin a backup key too, as a host with pins fails with
+    /// `HttpResponseError::CertificatePinMismatch` unless one of them
+    /// matches. `host` is compared with the URL's host, ignoring case. An
+    /// invalid pin is reported by `build`.
+    #[cfg(any(feature = "https", feature = "native-tls"))]
+    pub fn pin_spki_sha256(mut self, host: &str, pin: &str) -> Self {
+        if let Err(err) = self.tls.add_pin(host, pin) {
+            self.error.get_or_insert(err);
+        }
+        self
+    }
+
+    /// Sets whether `https` servers are trusted without verifying their
+    /// certificates at all. Off by default.
+    ///
+    /// **Dangerous**: any server, including one intercepting the connection,
+    /// is accepted as the host in the URL, which removes the protection TLS
+    /// is for. Only use this against servers you control, such as a staging
+    /// server with a self-signed certificate; `add_root_certificate` with
+    /// that certificate is the safe alternative. Pins are still enforced.
+    ///
+    /// This also lets rustls connect to IP addresses, which it otherwise
+    /// rejects as it cannot verify certificates for them; the server is then
+    /// sent a placeholder name for SNI.
+    #[cfg(any(feature = "https", feature = "native-tls"))]
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.tls.accept_invalid_certs = accept;
+        self
+    }
+
+    /// Sets whether `https` servers are trusted with a certificate that
+    /// chains to a trusted root but was issued for another host. Off by
+    /// default.
+    ///
+    /// **Dangerous**: any server holding a certificate from a trusted root,
+    /// for any name, is accepted as the host in the URL. Only use this for
+    /// servers you control that are reached under a name their certificate
+    /// lacks. With rustls, IP addresses are still rejected.
+    #[cfg(any(feature = "https", feature = "native-tls"))]
+    pub fn danger_accept_invalid_hostnames(mut self, accept: bool) -> Self {
+        self.tls.accept_invalid_hostnames = accept;
+        self
+    }
+
+    /// Sets the oldest TLS version negotiated with `https` servers, e.g.
+    /// `TlsVersion::Tls1_2` to refuse deprecated versions.
+    ///
+    /// By default the backend decides: rustls only implements TLS 1.2 and
+    /// 1.3, and `native-tls` starts at TLS 1.2 too. Versions the backend
+    /// cannot negotiate are left out, and `build` fails with
+    /// `HttpResponseError::UnsupportedTlsVersion` if none is left, e.g. for
+    /// TLS 1.0 with rustls.
+    #[cfg(any(feature = "https", feature = "native-tls"))]
+    pub fn min_tls_version(mut self, version: TlsVersion) -> Self {
+        self.tls.min_version = Some(version);
+        self
+    }
+
+    /// Sets the newest TLS version negotiated with `https` servers. See
+    /// `min_tls_version`.
+    #[cfg(any(feature = "https", feature = "native-tls"))]
+    pub fn max_tls_version(mut self, version: TlsVersion) -> Self {
+        self.tls.max_version = Some(version);
+        self
+    }
+
+    /// Builds the client.
+    pub fn build(self) -> Result<SimpleClient, HttpResponseError> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        #[cfg(any(feature = "https", feature = "native-tls"))]
+        let config = ClientConfig {
+            tls: if self.tls.is_default() {
+                None
+            } else {
+                Some(tls::connector(&self.tls)?)
+            },
+            ..self.config
+        };
+        #[cfg(not(any(feature = "https", feature = "native-tls")))]
+        let config = self.config;
+        Ok(SimpleClient {
+            config: Arc::new(config),
+        })
+    }
+}
+
+#[test]
+fn build_rejects_invalid_default_header() {
+    assert!(ClientBuilder::new()
+        .default_header("X-Ok", "fine")
+        .build()
+        .is_ok());
+    match ClientBuilder::new()
+        .default_header("X-Bad", "a\nInjected: yes")
+        .default_header("X-Ok", "fine")
+        .build()
+    {
+        Err(HttpResponseError::InvalidHeader(_)) => {}
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok(_) => panic!("invalid header was accepted"),
+    }
+}
+
+#[test]
+fn user_agent_replaces_default_header() {
+    let client = ClientBuilder::new()
+        .default_header("user-agent", "first")
+        .user_agent("glass-fi/0.1")
+        .build()
+        .unwrap();
+    assert_eq!(1, client.config.default_headers.len());
+    assert_eq!(
+        Some("glass-fi/0.1"),
+        client.config.default_headers.get("User-Agent")
+    );
+}
+
+#[cfg(any(feature = "https", feature = "native-tls"))]
+#[test]
+fn default_tls_settings_share_the_default_connector() {
+    let client = ClientBuilder::new().user_agent("x").build().unwrap();
+    assert!(client.config.tls.is_none());
+    let client = ClientBuilder::new()
+        .min_tls_version(TlsVersion::Tls1_2)
+        .build()
+        .unwrap();
+    assert!(client.config.tls.is_some());
+}
@@ -0,0 +1,121 @@
+#![deny(missing_docs)]
+
+use url::Url;
+use url::percent_encoding::percent_decode;
+
+use super::HttpResponseError;
+
+const DEFAULT_MEDIA_TYPE: &str = "text/plain;charset=US-ASCII";
+
+/// Decodes an RFC 2397 `data:` URL into its media type and payload.
+pub fn decode(url: &Url) -> Result<(String, Vec<u8>), HttpResponseError> {
+    // Everything after `data:` up to the fragment.
+    let content = &url.as_str()[url.scheme().len() + 1..];
+    let content = match content.find('#') {
+        Some(fragment) => &content[..fragment],
+        None => content,
+    };
+    let comma = content
+        .find(',')
+        .ok_or_else(|| HttpResponseError::InvalidDataUrl(url.to_string()))?;
+    let (meta, data) = (&content[..comma], &content[comma + 1..]);
+
+    let (media_type, is_base64) = match meta.rfind(';') {
+        Some(semicolon) if meta[semicolon + 1..].trim().eq_ignore_ascii_case("base64") => {
+            (meta[..semicolon].trim(), true)
+        }
+        _ => (meta.trim(), false),
+    };
+    let media_type = if media_type.is_empty() {
+        DEFAULT_MEDIA_TYPE.to_string()
+    } else if media_type.starts_with(';') {
+        // Parameters without a type, e.g. `data:;charset=utf-8,...`.
+        format!("text/plain{}", media_type)
+    } else {
+        percent_decode(media_type.as_bytes())
+            .decode_utf8_lossy()
+            .into_owned()
+    };
+
+    let data: Vec<u8> = percent_decode(data.as_bytes()).collect();
+    let data = if is_base64 {
+        base64_decode(&data).ok_or_else(|| HttpResponseError::InvalidDataUrl(url.to_string()))?
+    } else {
+        data
+    };
+    Ok((media_type, data))
+}
+
+pub(super) fn base64_decode(input: &[u8]) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len() * 3 / 4);
+    let mut accumulator = 0u32;
+    let mut bits = 0;
+    let mut padding = 0;
+    for &byte in input {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => {
+                padding += 1;
+                continue;
+            }
+            b' ' | b'\t' | b'\r' | b'\n' => continue,
+            _ => return None,
+        };
+        if padding > 0 {
+            return None;
+        }
+        accumulator = (accumulator << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((accumulator >> bits) as u8);
+            accumulator &= (1 << bits) - 1;
+        }
+    }
+    // Leftover bits must be zero padding from the final character.
+    if bits >= 6 || accumulator != 0 || padding > 2 {
+        return None;
+    }
+    Some(output)
+}
+
+#[test]
+fn decode_plain_and_base64() {
+    let url = Url::parse("data:,Hello%2C%20World!").unwrap();
+    assert_eq!(
+        (DEFAULT_MEDIA_TYPE.to_string(), b"Hello, World!".to_vec()),
+        decode(&url).unwrap()
+    );
+
+    let url = Url::parse("data:text/html;base64,PGI+aGk8L2I+#frag").unwrap();
+    assert_eq!(
+        ("text/html".to_string(), b"<b>hi</b>".to_vec()),
+        decode(&url).unwrap()
+    );
+
+    let url = Url::parse("data:;charset=utf-8,caf%C3%A9").unwrap();
+    let (media_type, data) = decode(&url).unwrap();
+    assert_eq!("text/plain;charset=utf-8", media_type);
+    assert_eq!("café", String::from_utf8(data).unwrap());
+}
+
+#[test]
+fn decode_rejects_malformed() {
+    assert!(decode(&Url::parse("data:text/plain").unwrap()).is_err());
+    assert!(decode(&Url::parse("data:;base64,not*base64").unwrap()).is_err());
+    assert!(decode(&Url::parse("data:;base64,QQ=x").unwrap()).is_err());
+}
+
+#[test]
+fn base64_lengths() {
+    assert_eq!(Some(b"".to_vec()), base64_decode(b""));
+    assert_eq!(Some(b"f".to_vec()), base64_decode(b"Zg=="));
+    assert_eq!(Some(b"fo".to_vec()), base64_decode(b"Zm8="));
+    assert_eq!(Some(b"foo".to_vec()), base64_decode(b"Zm9v"));
+    assert_eq!(Some(b"foob".to_vec()), base64_decode(b"Zm9vYg"));
+    assert_eq!(None, base64_decode(b"Z"));
+}
@@ -0,0 +1,354 @@
+#![deny(missing_docs)]
+
+use url;
+#[cfg(feature = "json")]
+use serde_json;
+
+use super::{StatusCode, UrlError};
+
+use std::error;
+use std::fmt;
+use std::convert;
+use std::io as stdio;
+
+/// Errors returned by the HTTP client.
+///
+/// Some variants exist only with a TLS feature enabled, and more may be added,
+/// so matches need a catch-all arm.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum HttpResponseError {
+    /// The URL does not use the `http` scheme, or `https` with the `https`
+    /// or `native-tls` feature.
+    NotHttpScheme,
+    /// The URL could not be parsed.
+    ParseURL(url::ParseError),
+    /// A `UrlBuilder` could not build its URL.
+    InvalidUrl(UrlError),
+    /// An I/O error occurred while talking to the server.
+    Io(stdio::Error),
+    /// The TLS handshake failed, e.g. because the server's certificate did
+    /// not verify.
+    #[cfg(any(feature = "https", feature = "native-tls"))]
+    Tls(stdio::Error),
+    /// A certificate or identity given to `ClientBuilder` could not be parsed
+    /// or used.
+    #[cfg(any(feature = "https", feature = "native-tls"))]
+    InvalidCertificate(String),
+    /// The server's certificate verified, but its public key matches none of
+    /// the pins set for the host with `ClientBuilder::pin_spki_sha256`;
+    /// holds the host.
+    #[cfg(any(feature = "https", feature = "native-tls"))]
+    CertificatePinMismatch(String),
+    /// The TLS versions allowed with `ClientBuilder::min_tls_version` and
+    /// `max_tls_version` are out of order, or include none the TLS backend
+    /// supports.
+    #[cfg(any(feature = "https", feature = "native-tls"))]
+    UnsupportedTlsVersion(String),
+    /// The URL did not resolve to any socket address.
+    InvalidSocketAddress,
+    /// The response did not start with an HTTP status line.
+    InvalidStatusLine(String),
+    /// A response header line could not be parsed.
+    InvalidHeader(String),
+    /// The `Content-Length` header is not a valid length.
+    InvalidContentLength(String),
+    /// A chunk-size line of a chunked body is malformed.
+    InvalidChunk(String),
+    /// The response breaks a protocol rule that `ParseMode::Strict` enforces.
+    ProtocolViolation(String),
+    /// A `data:` URL is missing its comma or has an invalid base64 payload.
+    InvalidDataUrl(String),
+    /// A request method is not a valid HTTP token.
+    InvalidMethod(String),
+    /// The server answered with an error status, see `HttpResponse::error_for_status`.
+    Status(StatusCode),
+    /// The request's deadline passed; holds the phase it had reached.
+    DeadlineExceeded(Phase),
+    /// The redirect limit was reached; holds the redirect that was not followed.
+    TooManyRedirects(String),
+    /// A `RedirectHook` denied a redirect; holds the redirect.
+    RedirectDenied(String),
+    /// No client of this name is registered in a `ClientRegistry`.
+    UnknownClient(String),
+    /// A JSON body could not be serialized or deserialized.
+    #[cfg(feature = "json")]
+    Json(serde_json::Error),
+}
+
+/// How far a request had progressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Resolving the host name.
+    Resolve,
+    /// Establishing the TCP connection.
+    Connect,
+    /// Writing the request head and body.
+    SendRequest,
+    /// Waiting for and reading the status line and headers.
+    ReadHead,
+    /// Reading the response body.
+    ReadBody,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Phase::Resolve => "resolving the host",
+            Phase::Connect => "connecting",
+            Phase::SendRequest => "sending the request",
+            Phase::ReadHead => "reading the response head",
+            Phase::ReadBody => "reading the response body",
+        })
+    }
+}
+
+impl HttpResponseError {
+    /// Returns `true` if the error was caused by an operation timing out or
+    /// the deadline passing.
+    pub fn is_timeout(&self) -> bool {
+        match *self {
+            HttpResponseError::Io(ref err) => err.kind() == stdio::ErrorKind::TimedOut,
+            HttpResponseError::DeadlineExceeded(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the error happened while establishing the
+    /// connection, including the connect timing out or the deadline passing
+    /// before it was established.
+    pub fn is_connect(&self) -> bool {
+        match *self {
+            HttpResponseError::Io(ref err) => {
+                matches!(
+                    err.kind(),
+                    stdio::ErrorKind::ConnectionRefused
+                        | stdio::ErrorKind::AddrNotAvailable
+                        | stdio::ErrorKind::NotConnected
+                ) || err
+                    .get_ref()
+                    .is_some_and(|inner| inner.is::<ConnectTimedOut>())
+            }
+            HttpResponseError::DeadlineExceeded(phase) => {
+                matches!(phase, Phase::Resolve | Phase::Connect)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the status code for `Status` errors.
+    pub fn status(&self) -> Option<StatusCode> {
+        match *self {
+            HttpResponseError::Status(status) => Some(status),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if sending the same request again may succeed.
+    ///
+    /// URL and scheme errors are never retryable, and neither is
+    /// `DeadlineExceeded`, as the request's whole budget is used up.
+    /// Timeouts and transient I/O failures are retryable, as are 408, 429,
+    /// 502, 503 and 504 responses.
+    pub fn is_retryable(&self) -> bool {
+        if let HttpResponseError::DeadlineExceeded(_) = *self {
+            return false;
+        }
+        if self.is_timeout() || self.is_connect() {
+            return true;
+        }
+        match *self {
+            HttpResponseError::Status(status) => {
+                matches!(status.as_u16(), 408 | 429 | 502 | 503 | 504)
+            }
+            HttpResponseError::Io(ref err) => matches!(
+                err.kind(),
+                stdio::ErrorKind::ConnectionReset
+                    | stdio::ErrorKind::ConnectionAborted
+                    | stdio::ErrorKind::BrokenPipe
+                    | stdio::ErrorKind::UnexpectedEof
+                    | stdio::ErrorKind::Interrupted
+            ),
+            _ => false,
+        }
+    }
+}
+
+// The cause of the `Io` error for a connect that timed out, which tells it
+// apart from other timeouts.
+#[derive(Debug)]
+pub(super) struct ConnectTimedOut;
+
+impl fmt::Display for ConnectTimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("connect timed out")
+    }
+}
+
+impl error::Error for ConnectTimedOut {}
+
+impl fmt::Display for HttpResponseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HttpResponseError::NotHttpScheme => {
+                write!(f, "Not HTTP Scheme: input string hasn't http scheme")
+            }
+            HttpResponseError::ParseURL(ref err) => write!(f, "Parse URL Error: {}", err),
+            HttpResponseError::InvalidUrl(ref err) => write!(f, "Invalid URL: {}", err),
+            HttpResponseError::Io(ref err) => write!(f, "IO Error: {}", err),
+            #[cfg(any(feature = "https", feature = "native-tls"))]
+            HttpResponseError::Tls(ref err) => write!(f, "TLS Error: {}", err),
+            #[cfg(any(feature = "https", feature = "native-tls"))]
+            HttpResponseError::InvalidCertificate(ref what) => {
+                write!(f, "Invalid certificate: {}", what)
+            }
+            #[cfg(any(feature = "https", feature = "native-tls"))]
+            HttpResponseError::CertificatePinMismatch(ref host) => {
+                write!(f, "Certificate pin mismatch: {}", host)
+            }
+            #[cfg(any(feature = "https", feature = "native-tls"))]
+            HttpResponseError::UnsupportedTlsVersion(ref what) => {
+                write!(f, "Unsupported TLS version: {}", what)
+            }
+            HttpResponseError::InvalidSocketAddress => write!(
+                f,
+                "Invalid socket address: socket address is invalid or nothing"
+            ),
+            HttpResponseError::InvalidStatusLine(ref line) => {
+                write!(f, "Invalid status line: {:?}", line)
+            }
+            HttpResponseError::InvalidHeader(ref line) => write!(f, "Invalid header: {:?}", line),
+            HttpResponseError::InvalidContentLength(ref value) => {
+                write!(f, "Invalid Content-Length: {:?}", value)
+            }
+            HttpResponseError::InvalidChunk(ref line) => write!(f, "Invalid chunk: {:?}", line),
+            HttpResponseError::ProtocolViolation(ref what) => {
+                write!(f, "Protocol violation: {}", what)
+            }
+            HttpResponseError::InvalidDataUrl(ref url) => write!(f, "Invalid data URL: {}", url),
+            HttpResponseError::InvalidMethod(ref method) => {
+                write!(f, "Invalid method: {:?}", method)
+            }
+            HttpResponseError::Status(status) => write!(f, "HTTP status error: {}", status),
+            HttpResponseError::DeadlineExceeded(phase) => {
+                write!(f, "Deadline exceeded while {}", phase)
+            }
+            HttpResponseError::TooManyRedirects(ref url) => {
+                write!(f, "Too many redirects: stopped before {}", url)
+            }
+            HttpResponseError::RedirectDenied(ref url) => write!(f, "Redirect denied: {}", url),
+            HttpResponseError::UnknownClient(ref name) => write!(f, "Unknown client: {}", name),
+            #[cfg(feature = "json")]
+            HttpResponseError::Json(ref err) => write!(f, "JSON Error: {}", err),
+        }
+    }
+}
+
+impl error::Error for HttpResponseError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            HttpResponseError::NotHttpScheme => None,
+            HttpResponseError::ParseURL(ref err) => Some(err),
+            HttpResponseError::InvalidUrl(ref err) => Some(err),
+            HttpResponseError::Io(ref err) => Some(err),
+            #[cfg(any(feature = "https", feature = "native-tls"))]
+            HttpResponseError::Tls(ref err) => Some(err),
+            #[cfg(any(feature = "https", feature = "native-tls"))]
+            HttpResponseError::InvalidCertificate(_) => None,
+            #[cfg(any(feature = "https", feature = "native-tls"))]
+            HttpResponseError::CertificatePinMismatch(_) => None,
+            #[cfg(any(feature = "https", feature = "native-tls"))]
+            HttpResponseError::UnsupportedTlsVersion(_) => None,
+            HttpResponseError::InvalidSocketAddress => None,
+            HttpResponseError::InvalidStatusLine(_) => None,
+            HttpResponseError::InvalidHeader(_) => None,
+            HttpResponseError::InvalidContentLength(_) => None,
+            HttpResponseError::InvalidChunk(_) => None,
+            HttpResponseError::ProtocolViolation(_) => None,
+            HttpResponseError::InvalidDataUrl(_) => None,
+            HttpResponseError::InvalidMethod(_) => None,
+            HttpResponseError::Status(_) => None,
+            HttpResponseError::DeadlineExceeded(_) => None,
+            HttpResponseError::TooManyRedirects(_) => None,
+            HttpResponseError::RedirectDenied(_) => None,
+            HttpResponseError::UnknownClient(_) => None,
+            #[cfg(feature = "json")]
+            HttpResponseError::Json(ref err) => Some(err),
+        }
+    }
+}
+
+impl convert::From<url::ParseError> for HttpResponseError {
+    fn from(err: url::ParseError) -> HttpResponseError {
+        HttpResponseError::ParseURL(err)
+    }
+}
+
+#[cfg(feature = "json")]
+impl convert::From<serde_json::Error> for HttpResponseError {
+    fn from(err: serde_json::Error) -> HttpResponseError {
+        HttpResponseError::Json(err)
+    }
+}
+
+impl convert::From<UrlError> for HttpResponseError {
+    fn from(err: UrlError) -> HttpResponseError {
+        HttpResponseError::InvalidUrl(err)
+    }
+}
+
+impl convert::From<stdio::Error> for HttpResponseError {
+    fn from(err: stdio::Error) -> HttpResponseError {
+        HttpResponseError::Io(err)
+    }
+}
+
+#[test]
+fn error_source_chain() {
+    use std::error::Error;
+
+    let err = HttpResponseError::from(url::Url::parse("not a url").unwrap_err());
+    assert!(err.source().is_some());
+    assert!(HttpResponseError::NotHttpScheme.source().is_none());
+    assert!(HttpResponseError::InvalidSocketAddress.source().is_none());
+}
+
+#[test]
+fn error_classification() {
+    let refused = HttpResponseError::from(stdio::Error::from(stdio::ErrorKind::ConnectionRefused));
+    assert!(refused.is_connect());
+    assert!(refused.is_retryable());
+    assert!(!refused.is_timeout());
+
+    let timed_out = HttpResponseError::from(stdio::Error::from(stdio::ErrorKind::TimedOut));
+    assert!(timed_out.is_timeout());
+    assert!(timed_out.is_retryable());
+
+    assert!(!HttpResponseError::NotHttpScheme.is_retryable());
+
+    let connect_timed_out = HttpResponseError::from(stdio::Error::new(
+        stdio::ErrorKind::TimedOut,
+        ConnectTimedOut,
+    ));
+    assert!(connect_timed_out.is_connect());
+    assert!(connect_timed_out.is_timeout());
+    assert!(!timed_out.is_connect());
+
+    for &(phase, connect) in &[
+        (Phase::Resolve, true),
+        (Phase::Connect, true),
+        (Phase::ReadHead, false),
+    ] {
+        let exceeded = HttpResponseError::DeadlineExceeded(phase);
+        assert_eq!(connect, exceeded.is_connect());
+        assert!(exceeded.is_timeout());
+        assert!(!exceeded.is_retryable());
+    }
+
+    let unavailable = HttpResponseError::Status(StatusCode::from_u16(503).unwrap());
+    assert_eq!(503, unavailable.status().unwrap().as_u16());
+    assert!(unavailable.is_retryable());
+    let not_found = HttpResponseError::Status(StatusCode::from_u16(404).unwrap());
+    assert!(!not_found.is_retryable());
+    assert!(refused.status().is_none());
+}
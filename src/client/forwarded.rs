@@ -0,0 +1,263 @@
+#![deny(missing_docs)]
+
+use std::fmt;
+
+use super::{HeaderMap, HttpResponseError};
+
+/// One element of an RFC 7239 `Forwarded` header: what a single proxy hop
+/// recorded about the request it forwarded.
+///
+/// Values are kept as written, e.g. `192.0.2.60`, `"[2001:db8::1]:4711"`
+/// unquoted to `[2001:db8::1]:4711`, `unknown` or an obfuscated `_hidden`
+/// identifier.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Forwarded {
+    /// `by`: the interface where the request came in to the proxy.
+    pub by: Option<String>,
+    /// `for`: the node that made the request to the proxy.
+    pub for_: Option<String>,
+    /// `host`: the `Host` the proxy received.
+    pub host: Option<String>,
+    /// `proto`: the scheme the proxy received the request over.
+    pub proto: Option<String>,
+    /// Extension parameters, in order, with their names lowercased.
+    pub extensions: Vec<(String, String)>,
+}
+
+impl Forwarded {
+    /// Parses a `Forwarded` header value into its elements, nearest hop
+    /// last.
+    ///
+    /// Parameter names are matched ignoring case, and each may appear once
+    /// per element. Empty elements, as in `for=a,,for=b`, are skipped.
+    pub fn parse(value: &str) -> Result<Vec<Forwarded>, HttpResponseError> {
+        let invalid = || HttpResponseError::InvalidHeader(format!("Forwarded: {}", value));
+        let mut elements = Vec::new();
+        let mut element = Forwarded::default();
+        let mut empty = true;
+        let mut rest = value.trim_start_matches(is_space);
+        while !rest.is_empty() {
+            if let Some(next) = rest.strip_prefix(',') {
+                if !empty {
+                    elements.push(element);
+                }
+                element = Forwarded::default();
+                empty = true;
+                rest = next.trim_start_matches(is_space);
+                continue;
+            }
+            if let Some(next) = rest.strip_prefix(';') {
+                rest = next.trim_start_matches(is_space);
+                continue;
+            }
+            let name_end = rest.find(|c| !is_tchar(c)).unwrap_or(rest.len());
+            let name = rest[..name_end].to_ascii_lowercase();
+            rest = rest[name_end..].strip_prefix('=').ok_or_else(invalid)?;
+            if name.is_empty() {
+                return Err(invalid());
+            }
+            let (parameter, next) = if rest.starts_with('"') {
+                unquote(rest).ok_or_else(invalid)?
+            } else {
+                let end = rest.find(|c| !is_tchar(c)).unwrap_or(rest.len());
+                (rest[..end].to_string(), &rest[end..])
+            };
+            if !element.set(name, parameter) {
+                return Err(invalid());
+            }
+            empty = false;
+            rest = next.trim_start_matches(is_space);
+            if !(rest.is_empty() || rest.starts_with(',') || rest.starts_with(';')) {
+                return Err(invalid());
+            }
+        }
+        if !empty {
+            elements.push(element);
+        }
+        Ok(elements)
+    }
+
+    /// Parses every `Forwarded` header in `headers`, in order.
+    pub fn from_headers(headers: &HeaderMap) -> Result<Vec<Forwarded>, HttpResponseError> {
+        let mut elements = Vec::new();
+        for value in headers.get_all("Forwarded") {
+            elements.extend(Forwarded::parse(value)?);
+        }
+        Ok(elements)
+    }
+
+    // Returns the element as a header value, or `None` if it has no
+    // parameters or a value that cannot be quoted.
+    pub(super) fn to_header_value(&self) -> Option<String> {
+        let value = self.to_string();
+        let valid = !value.is_empty()
+            && self
+                .extensions
+                .iter()
+                .all(|(name, _)| !name.is_empty() && name.chars().all(is_tchar))
+            && value
+                .bytes()
+                .all(|b| b == b'\t' || (b' '..=b'~').contains(&b) || b >= 0x80);
+        if valid {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    fn set(&mut self, name: String, value: String) -> bool {
+        let slot = match name.as_str() {
+            "by" => &mut self.by,
+            "for" => &mut self.for_,
+            "host" => &mut self.host,
+            "proto" => &mut self.proto,
+            _ => {
+                if self.extensions.iter().any(|(seen, _)| *seen == name) {
+                    return false;
+                }
+                self.extensions.push((name, value));
+                return true;
+            }
+        };
+        if slot.is_some() {
+            return false;
+        }
+        *slot = Some(value);
+        true
+    }
+}
+
+impl fmt::Display for Forwarded {
+    /// Writes the element as it appears in a `Forwarded` header, quoting
+    /// values that are not tokens, e.g. `for="[2001:db8::1]";proto=https`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let parameters = [
+            ("by", &self.by),
+            ("for", &self.for_),
+            ("host", &self.host),
+            ("proto", &self.proto),
+        ];
+        let known = parameters
+            .iter()
+            .filter_map(|&(name, value)| value.as_ref().map(|value| (name, value.as_str())));
+        let extensions = self
+            .extensions
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()));
+        for (index, (name, value)) in known.chain(extensions).enumerate() {
+            if index > 0 {
+                f.write_str(";")?;
+            }
+            write!(f, "{}=", name)?;
+            if !value.is_empty() && value.chars().all(is_tchar) {
+                f.write_str(value)?;
+            } else {
+                f.write_str("\"")?;
+                for c in value.chars() {
+                    if c == '"' || c == '\\' {
+                        f.write_str("\\")?;
+                    }
+                    write!(f, "{}", c)?;
+                }
+                f.write_str("\"")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_space(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
+
+// RFC 7230 `tchar`.
+fn is_tchar(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)
+}
+
+// Splits a leading `quoted-string` off `input`, returning its unescaped
+// contents and what follows it.
+fn unquote(input: &str) -> Option<(String, &str)> {
+    let mut value = String::new();
+    let mut chars = input.char_indices().skip(1);
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, &input[index + 1..])),
+            '\\' => value.push(chars.next()?.1),
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+#[test]
+fn parses_rfc7239_examples() {
+    let elements =
+        Forwarded::parse(r#"for="_gazonk", For="[2001:db8:cafe::17]:4711" , for=192.0.2.60;proto=http;by=203.0.113.43"#)
+            .unwrap();
+    assert_eq!(3, elements.len());
+    assert_eq!(Some("_gazonk"), elements[0].for_.as_deref());
+    assert_eq!(
+        Some("[2001:db8:cafe::17]:4711"),
+        elements[1].for_.as_deref()
+    );
+    assert_eq!(
+        Forwarded {
+            by: Some("203.0.113.43".to_string()),
+            for_: Some("192.0.2.60".to_string()),
+            proto: Some("http".to_string()),
+            ..Forwarded::default()
+        },
+        elements[2]
+    );
+
+    let elements =
+        Forwarded::parse(r#"for=unknown;secret="a \"b\", c";host=example.com,,"#).unwrap();
+    assert_eq!(1, elements.len());
+    assert_eq!(Some("example.com"), elements[0].host.as_deref());
+    assert_eq!(
+        vec![("secret".to_string(), "a \"b\", c".to_string())],
+        elements[0].extensions
+    );
+    assert!(Forwarded::parse("").unwrap().is_empty());
+
+    for invalid in &[
+        "for",
+        "for=a;for=b",
+        "=a",
+        "for=\"unterminated",
+        "for=a b",
+        "for=[::1]",
+        "x=1;X=2",
+    ] {
+        assert!(Forwarded::parse(invalid).is_err(), "{:?}", invalid);
+    }
+}
+
+#[test]
+fn serializes_with_quoting() {
+    let element = Forwarded {
+        for_: Some("[2001:db8::1]:80".to_string()),
+        proto: Some("https".to_string()),
+        extensions: vec![("note".to_string(), "say \"hi\"".to_string())],
+        ..Forwarded::default()
+    };
+    let value = element.to_header_value().unwrap();
+    assert_eq!(
+        r#"for="[2001:db8::1]:80";proto=https;note="say \"hi\"""#,
+        value
+    );
+    assert_eq!(vec![element], Forwarded::parse(&value).unwrap());
+
+    assert_eq!(None, Forwarded::default().to_header_value());
+    let injected = Forwarded {
+        host: Some("a\r\nX: y".to_string()),
+        ..Forwarded::default()
+    };
+    assert_eq!(None, injected.to_header_value());
+    let bad_name = Forwarded {
+        extensions: vec![("a=b".to_string(), "c".to_string())],
+        ..Forwarded::default()
+    };
+    assert_eq!(None, bad_name.to_header_value());
+}
@@ -0,0 +1,164 @@
+#![deny(missing_docs)]
+
+use std::slice;
+
+use super::HttpHeader;
+
+/// An ordered multimap of HTTP headers with case-insensitive names.
+///
+/// Headers keep the order and spelling they were received or inserted with;
+/// lookups ignore ASCII case.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeaderMap {
+    entries: Vec<HttpHeader>,
+}
+
+impl HeaderMap {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        HeaderMap::default()
+    }
+
+    /// Returns the number of header lines, counting repeated names separately.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there are no headers.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the first value for `name`.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|header| header.name.eq_ignore_ascii_case(name))
+            .map(|header| header.content.as_str())
+    }
+
+    /// Returns every value for `name`, in order.
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.entries
+            .iter()
+            .filter(move |header| header.name.eq_ignore_ascii_case(name))
+            .map(|header| header.content.as_str())
+    }
+
+    /// Returns `true` if at least one header is named `name`.
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Adds a header, keeping any existing values for the same name.
+    pub fn append<K: Into<String>, V: Into<String>>(&mut self, name: K, value: V) {
+        self.entries.push(HttpHeader {
+            name: name.into(),
+            content: value.into(),
+        });
+    }
+
+    /// Sets a header, replacing every existing value for the same name.
+    ///
+    /// The new value takes the position of the first value it replaces.
+    pub fn insert<K: Into<String>, V: Into<String>>(&mut self, name: K, value: V) {
+        let (name, value) = (name.into(), value.into());
+        match self
+            .entries
+            .iter()
+            .position(|header| header.name.eq_ignore_ascii_case(&name))
+        {
+            Some(first) => {
+                self.entries[first] = HttpHeader {
+                    name: name.clone(),
+                    content: value,
+                };
+                let mut index = 0;
+                self.entries.retain(|header| {
+                    index += 1;
+                    index - 1 <= first || !header.name.eq_ignore_ascii_case(&name)
+                });
+            }
+            None => self.append(name, value),
+        }
+    }
+
+    /// Removes every value for `name`, returning the first one.
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        let first = self.get(name).map(|value| value.to_string());
+        self.entries
+            .retain(|header| !header.name.eq_ignore_ascii_case(name));
+        first
+    }
+
+    /// Iterates over `(name, value)` pairs in order.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            inner: self.entries.iter(),
+        }
+    }
+}
+
+/// Iterator over the headers of a `HeaderMap`.
+#[derive(Debug)]
+pub struct Iter<'a> {
+    inner: slice::Iter<'a, HttpHeader>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a str, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next()
+            .map(|header| (header.name.as_str(), header.content.as_str()))
+    }
+}
+
+impl<'a> IntoIterator for &'a HeaderMap {
+    type Item = (&'a str, &'a str);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl From<Vec<HttpHeader>> for HeaderMap {
+    fn from(entries: Vec<HttpHeader>) -> Self {
+        HeaderMap { entries }
+    }
+}
+
+#[test]
+fn lookups_ignore_case() {
+    let mut headers = HeaderMap::new();
+    headers.append("ETag", "\"abc\"");
+    headers.append("Set-Cookie", "a=1");
+    headers.append("set-cookie", "b=2");
+    assert_eq!(Some("\"abc\""), headers.get("etag"));
+    assert!(headers.contains_key("SET-COOKIE"));
+    assert_eq!(
+        vec!["a=1", "b=2"],
+        headers.get_all("Set-Cookie").collect::<Vec<_>>()
+    );
+    assert_eq!(None, headers.get("Location"));
+    assert_eq!(3, headers.len());
+}
+
+#[test]
+fn insert_replaces_and_remove_clears() {
+    let mut headers = HeaderMap::new();
+    headers.append("Accept", "text/html");
+    headers.append("Vary", "Accept");
+    headers.append("accept", "text/plain");
+    headers.insert("ACCEPT", "*/*");
+    assert_eq!(
+        vec![("ACCEPT", "*/*"), ("Vary", "Accept")],
+        headers.iter().collect::<Vec<_>>()
+    );
+
+    assert_eq!(Some("*/*".to_string()), headers.remove("accept"));
+    assert_eq!(None, headers.remove("accept"));
+    assert_eq!(1, headers.len());
+}
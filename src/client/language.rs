@@ -0,0 +1,98 @@
+#![deny(missing_docs)]
+
+use super::HttpResponseError;
+
+// Builds an `Accept-Language` value from `locales`, most preferred first:
+// the first has the implicit weight 1 and each following one a lower
+// q-value.
+pub(super) fn accept_language<L: AsRef<str>>(locales: &[L]) -> Result<String, HttpResponseError> {
+    // Weights are in thousandths, the precision q-values allow.
+    let step = if locales.len() <= 10 {
+        100
+    } else {
+        999 / locales.len()
+    };
+    let mut value = String::new();
+    for (index, locale) in locales.iter().enumerate() {
+        let locale = locale.as_ref();
+        if !is_language_range(locale) {
+            return Err(HttpResponseError::InvalidHeader(format!(
+                "Accept-Language: {}",
+                locale
+            )));
+        }
+        if index > 0 {
+            value.push_str(", ");
+        }
+        value.push_str(locale);
+        if index > 0 {
+            let weight = format!("{:03}", 1000 - index * step);
+            value.push_str(";q=0.");
+            value.push_str(weight.trim_end_matches('0'));
+        }
+    }
+    Ok(value)
+}
+
+// Splits `Content-Language` values into their language tags.
+pub(super) fn content_language<'a, I>(values: I) -> Vec<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    values
+        .into_iter()
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+// RFC 4647 `language-range`: `*`, or subtags of one to eight letters and
+// digits joined by `-`, the first of them letters only.
+fn is_language_range(range: &str) -> bool {
+    if range == "*" {
+        return true;
+    }
+    range.split('-').enumerate().all(|(index, subtag)| {
+        (1..=8).contains(&subtag.len())
+            && subtag.bytes().all(|b| {
+                if index == 0 {
+                    b.is_ascii_alphabetic()
+                } else {
+                    b.is_ascii_alphanumeric()
+                }
+            })
+    })
+}
+
+#[test]
+fn accept_language_weights() {
+    assert_eq!(
+        "ja-JP, ja;q=0.9, en-US;q=0.8, *;q=0.7",
+        accept_language(&["ja-JP", "ja", "en-US", "*"]).unwrap()
+    );
+    assert_eq!("de", accept_language(&["de"]).unwrap());
+    assert_eq!("", accept_language::<&str>(&[]).unwrap());
+
+    let many: Vec<String> = (0..20)
+        .map(|i| format!("x{}", "a".repeat(i % 7 + 1)))
+        .collect();
+    let value = accept_language(&many).unwrap();
+    assert!(value.ends_with(";q=0.069"), "{}", value);
+
+    for invalid in &["", "en_US", "en-", "toolonglang", "en-US\r\nX: y", "1en"] {
+        match accept_language(&[invalid]) {
+            Err(HttpResponseError::InvalidHeader(_)) => {}
+            other => panic!("{:?} was accepted: {:?}", invalid, other),
+        }
+    }
+}
+
+#[test]
+fn content_language_tags() {
+    assert_eq!(
+        vec!["en", "ja-JP", "de"],
+        content_language(vec!["en, ja-JP", " ,de "])
+    );
+    assert!(content_language(Vec::new()).is_empty());
+}
@@ -0,0 +1,81 @@
+#![deny(missing_docs)]
+
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+use super::HttpResponseError;
+
+/// An HTTP request method.
+///
+/// The standard methods are available as constants; any other valid token can
+/// be used through `Method::from_str`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Method {
+    name: Cow<'static, str>,
+}
+
+impl Method {
+    /// `GET`
+    pub const GET: Method = Method::standard("GET");
+    /// `HEAD`
+    pub const HEAD: Method = Method::standard("HEAD");
+    /// `POST`
+    pub const POST: Method = Method::standard("POST");
+    /// `PUT`
+    pub const PUT: Method = Method::standard("PUT");
+    /// `DELETE`
+    pub const DELETE: Method = Method::standard("DELETE");
+    /// `PATCH`
+    pub const PATCH: Method = Method::standard("PATCH");
+    /// `OPTIONS`
+    pub const OPTIONS: Method = Method::standard("OPTIONS");
+
+    const fn standard(name: &'static str) -> Method {
+        Method {
+            name: Cow::Borrowed(name),
+        }
+    }
+
+    /// Returns the method name as sent on the request line.
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+}
+
+impl FromStr for Method {
+    type Err = HttpResponseError;
+
+    fn from_str(name: &str) -> Result<Method, HttpResponseError> {
+        if name.is_empty() || !name.bytes().all(is_token) {
+            return Err(HttpResponseError::InvalidMethod(name.to_string()));
+        }
+        Ok(Method {
+            name: Cow::Owned(name.to_string()),
+        })
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+// RFC 7230 `tchar`.
+fn is_token(byte: u8) -> bool {
+    match byte {
+        b'!' | b'#' | b'$' | b'%' | b'&' | b'\'' | b'*' | b'+' | b'-' | b'.' | b'^' | b'_'
+        | b'`' | b'|' | b'~' => true,
+        _ => byte.is_ascii_alphanumeric(),
+    }
+}
+
+#[test]
+fn parse_methods() {
+    assert_eq!(Method::GET, "GET".parse::<Method>().unwrap());
+    assert_eq!("PROPFIND", "PROPFIND".parse::<Method>().unwrap().as_str());
+    assert!("".parse::<Method>().is_err());
+    assert!("GET /".parse::<Method>().is_err());
+    assert!("GET\r\n".parse::<Method>().is_err());
+}
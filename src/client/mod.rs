@@ -1,2 +1,42 @@
 #![deny(missing_docs)]
+mod body;
+pub(crate) mod charset;
+mod client_builder;
+mod data_url;
+mod error;
+mod forwarded;
+mod header_map;
+mod language;
+mod method;
+mod parse_mode;
+mod profile;
+mod redirect;
+mod registry;
+mod request_builder;
 mod simple_client;
+mod status;
+mod streaming;
+#[cfg(any(feature = "https", feature = "native-tls"))]
+mod tls;
+mod transport;
+mod url_builder;
+
+pub use self::body::Body;
+#[cfg(any(feature = "https", feature = "native-tls"))]
+pub use self::tls::{Certificate, Identity, TlsInfo, TlsVersion};
+pub use self::client_builder::ClientBuilder;
+pub use self::error::{HttpResponseError, Phase};
+pub use self::forwarded::Forwarded;
+pub use self::header_map::HeaderMap;
+pub use self::method::Method;
+pub use self::parse_mode::{ParseMode, ProtocolWarning};
+pub use self::profile::{BrowserProfile, RequestProfile};
+pub use self::redirect::{RedirectAction, RedirectAttempt, RedirectHook, RedirectPolicy};
+pub use self::registry::ClientRegistry;
+pub use self::request_builder::RequestBuilder;
+pub use self::status::{StatusClass, StatusCode};
+pub use self::streaming::{BodyStream, StreamingResponse};
+pub use self::url_builder::{IntoUrl, UrlBuilder, UrlError};
+pub use self::simple_client::{
+    HttpBody, HttpHeader, HttpHeaders, HttpResponse, SimpleClient, TransferSizes,
+};
@@ -0,0 +1,52 @@
+#![deny(missing_docs)]
+
+use std::fmt;
+
+/// How strictly responses are parsed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Accept the protocol deviations browsers put up with: bare LF line
+    /// endings, whitespace around header names and repeated `Content-Length`
+    /// headers that agree.
+    #[default]
+    Lenient,
+    /// Reject those deviations with `HttpResponseError::ProtocolViolation`.
+    ///
+    /// Ambiguous framing is how request smuggling and response splitting
+    /// attacks get past intermediaries, so security-sensitive callers should
+    /// prefer this mode.
+    Strict,
+}
+
+/// A protocol deviation tolerated in `ParseMode::Lenient`, see
+/// `HttpResponse::warnings`.
+///
+/// Each of these would be rejected in `ParseMode::Strict`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtocolWarning {
+    /// `Content-Length` was sent more than once with the same value.
+    DuplicateContentLength,
+    /// `Content-Length` was sent together with `Transfer-Encoding`, which
+    /// takes precedence.
+    ContentLengthWithTransferEncoding,
+    /// A 205 Reset Content response announced content, which was ignored.
+    ContentWithResetContent,
+    /// A line ended in a bare LF instead of CRLF.
+    BareLineFeed,
+    /// A header name had whitespace around it, which was trimmed.
+    WhitespaceInHeaderName,
+}
+
+impl fmt::Display for ProtocolWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            ProtocolWarning::DuplicateContentLength => "duplicate Content-Length",
+            ProtocolWarning::ContentLengthWithTransferEncoding => {
+                "Content-Length with Transfer-Encoding"
+            }
+            ProtocolWarning::ContentWithResetContent => "content in a 205 Reset Content response",
+            ProtocolWarning::BareLineFeed => "bare LF line ending",
+            ProtocolWarning::WhitespaceInHeaderName => "whitespace in a header name",
+        })
+    }
+}
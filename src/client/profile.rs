@@ -0,0 +1,130 @@
+#![deny(missing_docs)]
+
+use std::fmt;
+
+use super::HeaderMap;
+
+/// Shapes the headers of every request a client sends, set with
+/// `ClientBuilder::profile`.
+///
+/// `apply` runs once the headers of a request are complete: defaults,
+/// request headers, `Host` and `Connection` have been merged. Framing headers
+/// are added after it.
+pub trait RequestProfile: fmt::Debug + Send + Sync {
+    /// Adds, replaces or reorders `headers` in place.
+    fn apply(&self, headers: &mut HeaderMap);
+}
+
+/// A `RequestProfile` that makes requests look like those of a desktop
+/// browser: its `User-Agent`, `Accept` and related headers, in its order.
+///
+/// Headers the request or the client already set keep their values but move
+/// to the browser's position; headers the browser would not send follow in
+/// their original order. `Accept-Encoding` is left out, as bodies are not
+/// decompressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrowserProfile {
+    /// Chrome on Windows.
+    Chrome,
+    /// Firefox on Windows.
+    Firefox,
+}
+
+impl BrowserProfile {
+    // Header names in the order the browser sends them, with the value to
+    // send when the request has none. `None` only fixes the position.
+    fn headers(self) -> &'static [(&'static str, Option<&'static str>)] {
+        match self {
+            BrowserProfile::Chrome => &[
+                ("Host", None),
+                ("Connection", None),
+                (
+                    "sec-ch-ua",
+                    Some("\"Chromium\";v=\"124\", \"Google Chrome\";v=\"124\", \"Not-A.Brand\";v=\"99\""),
+                ),
+                ("sec-ch-ua-mobile", Some("?0")),
+                ("sec-ch-ua-platform", Some("\"Windows\"")),
+                ("Upgrade-Insecure-Requests", Some("1")),
+                (
+                    "User-Agent",
+                    Some("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36"),
+                ),
+                (
+                    "Accept",
+                    Some("text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8,application/signed-exchange;v=b3;q=0.7"),
+                ),
+                ("Accept-Language", Some("en-US,en;q=0.9")),
+            ],
+            BrowserProfile::Firefox => &[
+                ("Host", None),
+                (
+                    "User-Agent",
+                    Some("Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:125.0) Gecko/20100101 Firefox/125.0"),
+                ),
+                (
+                    "Accept",
+                    Some("text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"),
+                ),
+                ("Accept-Language", Some("en-US,en;q=0.5")),
+                ("Connection", None),
+                ("Upgrade-Insecure-Requests", Some("1")),
+            ],
+        }
+    }
+}
+
+impl RequestProfile for BrowserProfile {
+    fn apply(&self, headers: &mut HeaderMap) {
+        let known = self.headers();
+        let mut shaped = HeaderMap::new();
+        for &(name, default) in known {
+            if headers.contains_key(name) {
+                for (set_name, value) in headers
+                    .iter()
+                    .filter(|&(set_name, _)| set_name.eq_ignore_ascii_case(name))
+                {
+                    shaped.append(set_name, value);
+                }
+            } else if let Some(value) = default {
+                shaped.append(name, value);
+            }
+        }
+        for (name, value) in headers.iter() {
+            if !known
+                .iter()
+                .any(|&(known, _)| known.eq_ignore_ascii_case(name))
+            {
+                shaped.append(name, value);
+            }
+        }
+        *headers = shaped;
+    }
+}
+
+#[test]
+fn browser_profile_orders_and_fills_headers() {
+    let mut headers = HeaderMap::new();
+    headers.append("Host", "example.com");
+    headers.append("Connection", "keep-alive");
+    headers.append("X-Trace", "1");
+    headers.append("accept", "application/json");
+    BrowserProfile::Firefox.apply(&mut headers);
+
+    let names: Vec<&str> = headers.iter().map(|(name, _)| name).collect();
+    assert_eq!(
+        vec![
+            "Host",
+            "User-Agent",
+            "accept",
+            "Accept-Language",
+            "Connection",
+            "Upgrade-Insecure-Requests",
+            "X-Trace"
+        ],
+        names
+    );
+    assert_eq!(Some("application/json"), headers.get("Accept"));
+    assert!(headers
+        .get("User-Agent")
+        .is_some_and(|agent| agent.contains("Firefox/")));
+}
@@ -0,0 +1,190 @@
+#![deny(missing_docs)]
+
+use std::fmt;
+
+use url::Url;
+
+use super::{HeaderMap, Method, StatusCode};
+
+/// Whether and how far a client follows redirects.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RedirectPolicy {
+    /// Return 3xx responses to the caller as they are.
+    #[default]
+    None,
+    /// Follow up to this many redirects per request, then fail with
+    /// `HttpResponseError::TooManyRedirects`.
+    ///
+    /// Only redirects to `http` URLs, and to `https` URLs with a TLS
+    /// feature enabled, are followed; others are returned as they are.
+    Limited(usize),
+}
+
+/// Decides about each redirect a client is about to follow, set with
+/// `ClientBuilder::redirect_hook`. Closures taking a `&mut RedirectAttempt`
+/// implement it.
+///
+/// Hooks only run for redirects the `RedirectPolicy` would follow, within
+/// its limit.
+pub trait RedirectHook: Send + Sync {
+    /// Returns whether to follow `attempt`, possibly after changing the
+    /// headers it will be sent with.
+    fn on_redirect(&self, attempt: &mut RedirectAttempt<'_>) -> RedirectAction;
+}
+
+impl<F> RedirectHook for F
+where
+    F: Fn(&mut RedirectAttempt<'_>) -> RedirectAction + Send + Sync,
+{
+    fn on_redirect(&self, attempt: &mut RedirectAttempt<'_>) -> RedirectAction {
+        self(attempt)
+    }
+}
+
+impl fmt::Debug for dyn RedirectHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("RedirectHook")
+    }
+}
+
+/// What a `RedirectHook` decided about a redirect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedirectAction {
+    /// Send the follow-up request.
+    Follow,
+    /// Return the redirect response to the caller as it is.
+    Stop,
+    /// Fail with `HttpResponseError::RedirectDenied`.
+    Deny,
+}
+
+/// A redirect about to be followed, as seen by a `RedirectHook`.
+#[derive(Debug)]
+pub struct RedirectAttempt<'a> {
+    pub(super) status: StatusCode,
+    pub(super) response_headers: &'a HeaderMap,
+    pub(super) previous: &'a Url,
+    pub(super) url: &'a Url,
+    pub(super) method: &'a Method,
+    pub(super) headers: &'a mut HeaderMap,
+    pub(super) followed: usize,
+}
+
+impl<'a> RedirectAttempt<'a> {
+    /// Returns the status of the redirect response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Returns the headers of the redirect response.
+    pub fn response_headers(&self) -> &HeaderMap {
+        self.response_headers
+    }
+
+    /// Returns the URL the redirect response came from.
+    pub fn previous_url(&self) -> &Url {
+        self.previous
+    }
+
+    /// Returns the URL the follow-up request goes to.
+    pub fn url(&self) -> &Url {
+        self.url
+    }
+
+    /// Returns the method of the follow-up request.
+    pub fn method(&self) -> &Method {
+        self.method
+    }
+
+    /// Returns the headers of the follow-up request, including the client's
+    /// defaults.
+    ///
+    /// Credentials and any explicit `Host` have already been removed if the
+    /// origin changes, as have the body headers if the body is dropped.
+    pub fn headers(&self) -> &HeaderMap {
+        self.headers
+    }
+
+    /// Returns the headers of the follow-up request for changing, e.g. to
+    /// sign it for the new URL. See `headers`.
+    ///
+    /// Invalid headers make the request fail with
+    /// `HttpResponseError::InvalidHeader`.
+    pub fn headers_mut(&mut self) -> &mut HeaderMap {
+        self.headers
+    }
+
+    /// Returns how many redirects were followed before this one.
+    pub fn followed(&self) -> usize {
+        self.followed
+    }
+}
+
+// What to send next after following a redirect.
+pub(super) struct Redirect {
+    pub(super) url: Url,
+    pub(super) method: Method,
+    pub(super) keep_body: bool,
+}
+
+// Returns where a response with `status` and `headers` to a `method` request
+// for `url` points, if it is a redirect with a usable `Location`: one that
+// resolves to a URL the client can request from a server. Redirects to any
+// other scheme, `data:` included, are returned to the caller unfollowed.
+//
+// 303, and 301 or 302 after a `POST`, turn into a body-less `GET` as browsers
+// do; 307 and 308 repeat the request unchanged.
+pub(super) fn next_request(
+    method: &Method,
+    url: &Url,
+    status: StatusCode,
+    headers: &HeaderMap,
+) -> Option<Redirect> {
+    let code = status.as_u16();
+    if !matches!(code, 301 | 302 | 303 | 307 | 308) {
+        return None;
+    }
+    let next = url.join(headers.get("Location")?).ok()?;
+    if !follows_scheme(next.scheme()) {
+        return None;
+    }
+    let becomes_get = match code {
+        303 => *method != Method::HEAD,
+        301 | 302 => *method == Method::POST,
+        _ => false,
+    };
+    Some(Redirect {
+        url: next,
+        method: if becomes_get {
+            Method::GET
+        } else {
+            method.clone()
+        },
+        keep_body: !becomes_get,
+    })
+}
+
+fn follows_scheme(scheme: &str) -> bool {
+    match scheme {
+        "http" => true,
+        #[cfg(any(feature = "https", feature = "native-tls"))]
+        "https" => true,
+        _ => false,
+    }
+}
+
+// Drops request headers that must not follow the request to `next`: the body
+// headers when the body is dropped, and credentials and any explicit `Host`
+// when the origin changes.
+pub(super) fn strip_headers(headers: &mut HeaderMap, previous: &Url, next: &Url, keep_body: bool) {
+    if !keep_body {
+        headers.remove("Content-Type");
+        headers.remove("Content-Length");
+    }
+    if previous.origin() != next.origin() {
+        headers.remove("Authorization");
+        headers.remove("Cookie");
+        headers.remove("Proxy-Authorization");
+        headers.remove("Host");
+    }
+}
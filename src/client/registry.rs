@@ -0,0 +1,204 @@
+#![deny(missing_docs)]
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+
+use super::{HttpResponseError, SimpleClient};
+
+type Init = Box<dyn Fn() -> Result<SimpleClient, HttpResponseError> + Send + Sync>;
+
+// A registered client, built by `init` on first use.
+struct Entry {
+    client: Mutex<Option<SimpleClient>>,
+    init: Init,
+}
+
+#[derive(Default)]
+struct Clients {
+    by_name: HashMap<String, Arc<Entry>>,
+    shut_down: bool,
+}
+
+/// Named, pre-configured clients shared across an application, such as
+/// `"github"` or `"internal-billing"`.
+///
+/// Clients registered with `register` are built on first use, so a registry
+/// can be filled at startup without building clients that are never used.
+/// `ClientRegistry::global` is a process-wide registry for clients that are
+/// needed everywhere.
+#[derive(Default)]
+pub struct ClientRegistry {
+    clients: RwLock<Clients>,
+}
+
+impl ClientRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        ClientRegistry::default()
+    }
+
+    /// Returns the process-wide registry.
+    pub fn global() -> &'static ClientRegistry {
+        static GLOBAL: OnceLock<ClientRegistry> = OnceLock::new();
+        GLOBAL.get_or_init(ClientRegistry::new)
+    }
+
+    /// Registers `init` to build the client called `name` when it is first
+    /// retrieved, replacing any client of that name.
+    ///
+    /// If `init` fails, `get` returns its error and `init` is tried again on
+    /// the next call. It must not retrieve the client it builds.
+    pub fn register<N, F>(&self, name: N, init: F)
+    where
+        N: Into<String>,
+        F: Fn() -> Result<SimpleClient, HttpResponseError> + Send + Sync + 'static,
+    {
+        self.add(
+            name.into(),
+            Entry {
+                client: Mutex::new(None),
+                init: Box::new(init),
+            },
+        );
+    }
+
+    /// Registers `client` as `name`, replacing any client of that name.
+    pub fn insert<N: Into<String>>(&self, name: N, client: SimpleClient) {
+        let clone = client.clone();
+        self.add(
+            name.into(),
+            Entry {
+                client: Mutex::new(Some(client)),
+                init: Box::new(move || Ok(clone.clone())),
+            },
+        );
+    }
+
+    /// Returns the client called `name`, building it first if it was
+    /// registered with `register` and has not been built yet.
+    ///
+    /// Fails with `HttpResponseError::UnknownClient` if no client has that
+    /// name, or the registry was shut down.
+    pub fn get(&self, name: &str) -> Result<SimpleClient, HttpResponseError> {
+        let entry = self
+            .read()
+            .by_name
+            .get(name)
+            .cloned()
+            .ok_or_else(|| HttpResponseError::UnknownClient(name.to_string()))?;
+        let mut client = entry.client.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(ref client) = *client {
+            return Ok(client.clone());
+        }
+        let built = (entry.init)()?;
+        *client = Some(built.clone());
+        Ok(built)
+    }
+
+    /// Returns `true` if a client called `name` is registered, built or not.
+    pub fn contains(&self, name: &str) -> bool {
+        self.read().by_name.contains_key(name)
+    }
+
+    /// Returns the names of the registered clients, in alphabetical order.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<_> = self.read().by_name.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Drops every client and initializer, as at application shutdown.
+    ///
+    /// Afterwards `get` fails for every name and new registrations are
+    /// ignored. Clones already handed out keep working; a client holds no
+    /// connections between requests, so there is nothing else to close.
+    pub fn shutdown_all(&self) {
+        let mut clients = self.write();
+        clients.by_name.clear();
+        clients.shut_down = true;
+    }
+
+    fn add(&self, name: String, entry: Entry) {
+        let mut clients = self.write();
+        if !clients.shut_down {
+            clients.by_name.insert(name, Arc::new(entry));
+        }
+    }
+
+    // A panicking initializer runs outside these locks, so a poisoned lock
+    // still holds a consistent map.
+    fn read(&self) -> ::std::sync::RwLockReadGuard<'_, Clients> {
+        self.clients.read().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn write(&self) -> ::std::sync::RwLockWriteGuard<'_, Clients> {
+        self.clients.write().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl fmt::Debug for ClientRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ClientRegistry")
+            .field("names", &self.names())
+            .finish()
+    }
+}
+
+#[test]
+fn clients_are_built_once_on_first_use() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let builds = Arc::new(AtomicUsize::new(0));
+    let registry = ClientRegistry::new();
+    let counter = builds.clone();
+    registry.register("github", move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        SimpleClient::builder().user_agent("glass-fi").build()
+    });
+    registry.insert("plain", SimpleClient::new());
+    assert_eq!(vec!["github", "plain"], registry.names());
+    assert_eq!(0, builds.load(Ordering::SeqCst));
+
+    let client = registry.get("github").unwrap();
+    assert_eq!(
+        Some("glass-fi"),
+        client.config.default_headers.get("User-Agent")
+    );
+    registry.get("github").unwrap();
+    assert_eq!(1, builds.load(Ordering::SeqCst));
+    assert!(registry.get("plain").is_ok());
+    match registry.get("billing") {
+        Err(HttpResponseError::UnknownClient(ref name)) if name == "billing" => {}
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    }
+
+    registry.shutdown_all();
+    assert!(!registry.contains("github"));
+    registry.insert("late", SimpleClient::new());
+    assert!(registry.get("late").is_err());
+}
+
+#[test]
+fn failed_initialization_is_retried() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    let fail = Arc::new(AtomicBool::new(true));
+    let registry = ClientRegistry::new();
+    let failing = fail.clone();
+    registry.register("billing", move || {
+        if failing.load(Ordering::SeqCst) {
+            SimpleClient::builder()
+                .default_header("X-Bad", "a\nb")
+                .build()
+        } else {
+            Ok(SimpleClient::new())
+        }
+    });
+    match registry.get("billing") {
+        Err(HttpResponseError::InvalidHeader(_)) => {}
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    }
+    fail.store(false, Ordering::SeqCst);
+    assert!(registry.get("billing").is_ok());
+}
@@ -0,0 +1,352 @@
+#![deny(missing_docs)]
+
+use std::borrow::Borrow;
+use std::time::Duration;
+
+#[cfg(feature = "json")]
+use serde::Serialize;
+#[cfg(feature = "json")]
+use serde_json;
+use url::form_urlencoded;
+use url::Url;
+
+#[cfg(test)]
+use super::body::BodyKind;
+use super::language;
+use super::{
+    Body, Forwarded, HttpHeader, HttpResponse, HttpResponseError, IntoUrl, Method, SimpleClient,
+    StreamingResponse,
+};
+
+/// A request being composed, created by `SimpleClient::request`.
+///
+/// Errors in the URL or in headers are deferred until `send`.
+pub struct RequestBuilder {
+    client: SimpleClient,
+    pub(super) method: Method,
+    pub(super) url: Result<Url, HttpResponseError>,
+    pub(super) headers: Vec<HttpHeader>,
+    pub(super) body: Option<Body>,
+    pub(super) timeout: Option<Duration>,
+    pub(super) deadline: Option<Duration>,
+    error: Option<HttpResponseError>,
+}
+
+impl RequestBuilder {
+    pub(super) fn new<U: IntoUrl>(client: SimpleClient, method: Method, url: U) -> Self {
+        RequestBuilder {
+            client,
+            method,
+            url: url.into_url(),
+            headers: Vec::new(),
+            body: None,
+            timeout: None,
+            deadline: None,
+            error: None,
+        }
+    }
+
+    /// Adds a header to the request. Repeating a name sends it more than once.
+    ///
+    /// Setting `Host` or `Connection` replaces the value the client would
+    /// otherwise send.
+    pub fn header<K: Into<String>, V: Into<String>>(mut self, name: K, value: V) -> Self {
+        match validate_header(name.into(), value.into()) {
+            Ok(header) => self.headers.push(header),
+            Err(err) => {
+                self.error.get_or_insert(err);
+            }
+        }
+        self
+    }
+
+    /// Sets the request body, either bytes in memory or a `Body` that is read
+    /// while the request is sent.
+    ///
+    /// The `Content-Length` or `Transfer-Encoding` header is added when
+    /// sending. A streamed body cannot be sent twice, so a 307 or 308
+    /// redirect in answer to it is returned rather than followed.
+    pub fn body<B: Into<Body>>(mut self, body: B) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    /// Sets an `application/x-www-form-urlencoded` body built from `pairs`,
+    /// encoded as for `query`, and the matching `Content-Type`.
+    ///
+    /// Any `Content-Type` set earlier is replaced.
+    pub fn form<K, V, P>(mut self, pairs: &[P]) -> Self
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+        P: Borrow<(K, V)>,
+    {
+        let mut serializer = form_urlencoded::Serializer::new(String::new());
+        for pair in pairs {
+            let (ref key, ref value) = *pair.borrow();
+            serializer.append_pair(key.as_ref(), value.as_ref());
+        }
+        self.set_content_type("application/x-www-form-urlencoded");
+        self.body(serializer.finish())
+    }
+
+    /// Sets a JSON body serialized from `value`, and `Content-Type:
+    /// application/json`. Needs the `json` feature.
+    ///
+    /// Any `Content-Type` set earlier is replaced. Serialization errors are
+    /// returned from `send`.
+    #[cfg(feature = "json")]
+    pub fn json<T: Serialize + ?Sized>(mut self, value: &T) -> Self {
+        match serde_json::to_vec(value) {
+            Ok(body) => {
+                self.set_content_type("application/json");
+                self.body(body)
+            }
+            Err(err) => {
+                self.error.get_or_insert(HttpResponseError::from(err));
+                self
+            }
+        }
+    }
+
+    /// Appends query parameters to the URL, after any it already has.
+    ///
+    /// Names and values are encoded as `application/x-www-form-urlencoded`:
+    /// spaces become `+`, and `&`, `=`, `+`, `#` and non-ASCII characters are
+    /// percent-encoded. Calling it again appends more parameters.
+    pub fn query<K, V, P>(mut self, pairs: &[P]) -> Self
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+        P: Borrow<(K, V)>,
+    {
+        if let Ok(ref mut url) = self.url {
+            if !pairs.is_empty() {
+                let mut serializer = url.query_pairs_mut();
+                for pair in pairs {
+                    let (ref key, ref value) = *pair.borrow();
+                    serializer.append_pair(key.as_ref(), value.as_ref());
+                }
+            }
+        }
+        self
+    }
+
+    /// Sets `Accept-Language` from `locales`, most preferred first, e.g.
+    /// `["ja-JP", "ja", "en"]` becomes `ja-JP, ja;q=0.9, en;q=0.8`.
+    ///
+    /// Replaces any `Accept-Language` set earlier or by the client. Locales
+    /// that are not valid language ranges are reported by `send`.
+    pub fn accept_language<L: AsRef<str>>(mut self, locales: &[L]) -> Self {
+        match language::accept_language(locales) {
+            Ok(value) => self.replace_header("Accept-Language", &value),
+            Err(err) => {
+                self.error.get_or_insert(err);
+            }
+        }
+        self
+    }
+
+    /// Appends `hop` to the request's `Forwarded` header, adding one if
+    /// there is none, as a proxy does when passing a request on.
+    ///
+    /// An element with no parameters, or with a value that cannot be sent
+    /// even quoted, is reported by `send`.
+    pub fn forwarded(mut self, hop: &Forwarded) -> Self {
+        let value = match hop.to_header_value() {
+            Some(value) => value,
+            None => {
+                self.error
+                    .get_or_insert(HttpResponseError::InvalidHeader(format!(
+                        "Forwarded: {}",
+                        hop
+                    )));
+                return self;
+            }
+        };
+        match self
+            .headers
+            .iter_mut()
+            .rev()
+            .find(|header| header.name.eq_ignore_ascii_case("Forwarded"))
+        {
+            Some(header) => {
+                header.content.push_str(", ");
+                header.content.push_str(&value);
+            }
+            None => self.headers.push(HttpHeader {
+                name: "Forwarded".to_string(),
+                content: value,
+            }),
+        }
+        self
+    }
+
+    /// Fails the request when connecting, or any single read or write, makes
+    /// no progress for `timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Fails the request with `HttpResponseError::DeadlineExceeded` unless the
+    /// whole exchange, from resolving the host to reading the last byte of
+    /// the body, completes within `budget`.
+    ///
+    /// Unlike `timeout`, steady but slow progress does not extend the budget.
+    pub fn deadline(mut self, budget: Duration) -> Self {
+        self.deadline = Some(budget);
+        self
+    }
+
+    fn set_content_type(&mut self, content_type: &str) {
+        self.replace_header("Content-Type", content_type);
+    }
+
+    fn replace_header(&mut self, name: &str, value: &str) {
+        self.headers
+            .retain(|header| !header.name.eq_ignore_ascii_case(name));
+        self.headers.push(HttpHeader {
+            name: name.to_string(),
+            content: value.to_string(),
+        });
+    }
+
+    /// Sends the request and waits for the response.
+    pub fn send(mut self) -> Result<HttpResponse, HttpResponseError> {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        let client = self.client.clone();
+        client.execute(self)
+    }
+
+    /// Sends the request and returns as soon as the response head has
+    /// arrived, leaving the body to be read through
+    /// `StreamingResponse::bytes_stream`.
+    ///
+    /// Redirects, timeouts and the deadline apply as for `send`; the deadline
+    /// keeps running while the body is read. The body is read on a thread of
+    /// its own.
+    pub fn send_streaming(mut self) -> Result<StreamingResponse, HttpResponseError> {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        let client = self.client.clone();
+        client.execute_streaming(self)
+    }
+}
+
+// Rejects names that are not RFC 7230 tokens and values that could end the
+// header line early.
+pub(super) fn validate_header(
+    name: String,
+    value: String,
+) -> Result<HttpHeader, HttpResponseError> {
+    if !is_valid_header_name(&name) {
+        Err(HttpResponseError::InvalidHeader(name))
+    } else if value.bytes().any(|b| b == b'\r' || b == b'\n' || b == 0) {
+        Err(HttpResponseError::InvalidHeader(format!(
+            "{}: {}",
+            name, value
+        )))
+    } else {
+        Ok(HttpHeader {
+            name,
+            content: value,
+        })
+    }
+}
+
+// RFC 7230 `token`.
+fn is_valid_header_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+#[test]
+fn rejects_header_injection() {
+    let client = SimpleClient::new();
+    let request = client
+        .request(Method::GET, "http://127.0.0.1/")
+        .header("X-Ok", "fine")
+        .header("X-Bad", "a\r\nInjected: yes");
+    assert_eq!(1, request.headers.len());
+    match request.send() {
+        Err(HttpResponseError::InvalidHeader(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let request = client
+        .request(Method::GET, "http://127.0.0.1/")
+        .header("Bad Name", "x");
+    assert!(request.send().is_err());
+}
+
+#[test]
+fn query_is_appended() {
+    let request = SimpleClient::new()
+        .request(Method::GET, "http://127.0.0.1/search?lang=en")
+        .query(&[("q", "rust lang"), ("page", "2")]);
+    assert_eq!(
+        Some("lang=en&q=rust+lang&page=2"),
+        request.url.as_ref().unwrap().query()
+    );
+}
+
+#[test]
+fn query_edge_cases() {
+    let query = |url: &str, pairs: &[(&str, &str)]| {
+        SimpleClient::new()
+            .request(Method::GET, url)
+            .query(pairs)
+            .url
+            .unwrap()
+            .to_string()
+    };
+    assert_eq!("http://127.0.0.1/", query("http://127.0.0.1/", &[]));
+    assert_eq!("http://127.0.0.1/?", query("http://127.0.0.1/?", &[]));
+    assert_eq!(
+        "http://127.0.0.1/?a=1",
+        query("http://127.0.0.1/?", &[("a", "1")])
+    );
+    assert_eq!(
+        "http://127.0.0.1/?k=a%2Bb%26c%3Dd%23e&caf%C3%A9=%E2%9C%93",
+        query("http://127.0.0.1/", &[("k", "a+b&c=d#e"), ("café", "✓")])
+    );
+    assert_eq!(
+        "http://127.0.0.1/p?x=1&x=2&empty=#frag",
+        query("http://127.0.0.1/p?x=1#frag", &[("x", "2"), ("empty", "")])
+    );
+
+    let request = SimpleClient::new()
+        .request(Method::GET, "http://127.0.0.1/")
+        .query(&[("a", "1")])
+        .query(&[(String::from("b"), String::from("2"))]);
+    assert_eq!(Some("a=1&b=2"), request.url.as_ref().unwrap().query());
+}
+
+#[test]
+fn form_sets_body_and_content_type() {
+    let request = SimpleClient::new()
+        .request(Method::POST, "http://127.0.0.1/login")
+        .header("content-type", "text/plain")
+        .header("Accept", "*/*")
+        .form(&[("user", "a b"), ("pass", "p&ss=✓")]);
+    match request.body.as_ref().map(|body| &body.kind) {
+        Some(BodyKind::Bytes(bytes)) => {
+            assert_eq!(&b"user=a+b&pass=p%26ss%3D%E2%9C%93"[..], &bytes[..])
+        }
+        _ => panic!("form body is not in memory"),
+    }
+    let content_types: Vec<&str> = request
+        .headers
+        .iter()
+        .filter(|header| header.name.eq_ignore_ascii_case("Content-Type"))
+        .map(|header| header.content.as_str())
+        .collect();
+    assert_eq!(vec!["application/x-www-form-urlencoded"], content_types);
+    assert_eq!(2, request.headers.len());
+}
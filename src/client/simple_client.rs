@@ -1,152 +1,336 @@
 #![deny(missing_docs)]
 
-use tokio;
+use bytes::Bytes;
 use tokio::prelude::*;
-use tokio::runtime::Runtime;
-use tokio::net::{ConnectFuture, TcpStream};
+use tokio::prelude::future;
+use tokio::runtime::current_thread::Runtime;
+use tokio::net::TcpStream;
 use tokio::io;
-use std::net::ToSocketAddrs;
-use std::{thread, time};
+use tokio::sync::mpsc;
+use tokio::timer::{Delay, Timeout};
+use std::net::{SocketAddr, ToSocketAddrs};
 use std::cmp;
 use std::io::BufRead;
-use std::sync::{Arc, Mutex};
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::mpsc as std_mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use url::{self, Host, Url};
+use url::{Host, Url};
+#[cfg(feature = "json")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "json")]
+use serde_json;
 
-use std::error;
-use std::fmt;
-use std::convert;
 use std::io as stdio;
 
+use super::body::{BodyKind, Streamed};
+use super::client_builder::ClientConfig;
+use super::error::ConnectTimedOut;
+use super::redirect::{self, RedirectAction, RedirectAttempt, RedirectPolicy};
+use super::request_builder::validate_header;
+use super::{
+    Body, ClientBuilder, HeaderMap, HttpResponseError, IntoUrl, Method, ParseMode, Phase,
+    ProtocolWarning, RequestBuilder,
+};
+use super::charset;
+use super::language;
+use super::data_url;
+use proto::h1::{self, BodyFraming};
+use super::status::{self, StatusCode};
+#[cfg(any(feature = "https", feature = "native-tls"))]
+use super::tls::{self, TlsInfo};
+use super::transport::Transport;
+use super::streaming::{BodyStream, StreamingResponse};
+
+/// Body of an HTTP response.
 #[derive(Debug)]
-struct HttpBody {
-    text: String,
+pub struct HttpBody {
+    /// Body decoded as by `HttpResponse::text`.
+    ///
+    /// Use `HttpResponse::bytes` for the body exactly as received.
+    pub text: String,
 }
 
-#[derive(Debug, Clone)]
-struct HttpHeader {
-    name: String,
-    content: String,
+/// A single response header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpHeader {
+    /// Header name as sent by the server.
+    pub name: String,
+    /// Header value with surrounding whitespace removed.
+    pub content: String,
 }
 
+/// Response headers in the order they were received.
 #[derive(Debug)]
-struct HttpHeaders {
+pub struct HttpHeaders {
     inner: Vec<HttpHeader>,
 }
 
+impl HttpHeaders {
+    /// Returns an iterator over the headers without consuming them.
+    pub fn iter(&self) -> ::std::slice::Iter<'_, HttpHeader> {
+        self.inner.iter()
+    }
+}
+
+impl<'a> From<&'a HeaderMap> for HttpHeaders {
+    fn from(headers: &'a HeaderMap) -> Self {
+        HttpHeaders {
+            inner: headers
+                .iter()
+                .map(|(name, content)| HttpHeader {
+                    name: name.to_string(),
+                    content: content.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
 impl Iterator for HttpHeaders {
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.clone().into_iter().next()
+        if self.inner.is_empty() {
+            None
+        } else {
+            Some(self.inner.remove(0))
+        }
     }
 
     type Item = HttpHeader;
 }
+
+/// Number of bytes a request and its response occupied on the wire.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransferSizes {
+    /// Bytes of the request line and request headers.
+    pub header_bytes_sent: u64,
+    /// Bytes of the request body.
+    pub body_bytes_sent: u64,
+    /// Bytes of the status line and response headers, including the blank line.
+    pub header_bytes_received: u64,
+    /// Bytes of the response body as received, before any decoding.
+    pub body_bytes_received: u64,
+}
+
+/// A response returned by `SimpleClient`.
 #[derive(Debug)]
-struct HttpResponse {
-    head: HttpHeaders,
-    body: HttpBody,
+pub struct HttpResponse {
+    /// Status code from the status line.
+    pub status: StatusCode,
+    /// Reason phrase from the status line, exactly as sent by the server.
+    pub reason: String,
+    /// Response headers, for lookups by name.
+    pub headers: HeaderMap,
+    /// Response headers as an iterator, in the order they were received.
+    pub head: HttpHeaders,
+    /// Response body as text.
+    pub body: HttpBody,
+    bytes: Bytes,
+    url: Url,
+    sizes: TransferSizes,
+    warnings: Vec<ProtocolWarning>,
+    #[cfg(any(feature = "https", feature = "native-tls"))]
+    tls: Option<TlsInfo>,
 }
 
 impl HttpResponse {
-    fn new<S: Into<String>>(head: HttpHeaders, body_text: S) -> Self {
+    fn new(url: Url, head: ResponseHead, body: Vec<u8>, sizes: TransferSizes) -> Self {
+        let text = charset::decode(&body, head.headers.get("Content-Type")).into_owned();
         HttpResponse {
-            head,
-            body: HttpBody {
-                text: body_text.into(),
-            },
+            url,
+            status: head.status,
+            reason: head.reason,
+            head: HttpHeaders::from(&head.headers),
+            headers: head.headers,
+            warnings: head.warnings,
+            #[cfg(any(feature = "https", feature = "native-tls"))]
+            tls: head.tls,
+            body: HttpBody { text },
+            bytes: Bytes::from(body),
+            sizes,
         }
     }
-}
 
-#[derive(Debug)]
-enum HttpResponseError {
-    NotHttpScheme,
-    ParseURL(url::ParseError),
-    Io(stdio::Error),
-    InvalidSocketAddress,
-}
-
-impl fmt::Display for HttpResponseError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            HttpResponseError::NotHttpScheme => {
-                write!(f, "Not HTTP Scheme: input string hasn't http scheme")
-            }
-            HttpResponseError::ParseURL(ref err) => write!(f, "Parse URL Error: {}", err),
-            HttpResponseError::Io(ref err) => write!(f, "IO Error: {}", err),
-            HttpResponseError::InvalidSocketAddress => write!(
-                f,
-                "Invalid socket address: socket address is invalid or nothing"
-            ),
-        }
+    /// Returns the body exactly as received.
+    ///
+    /// Cloning the returned `Bytes` is cheap and does not copy the body.
+    pub fn bytes(&self) -> &Bytes {
+        &self.bytes
     }
-}
 
-impl error::Error for HttpResponseError {
-    fn description(&self) -> &str {
-        match *self {
-            HttpResponseError::NotHttpScheme => "This hasn't http scheme",
-            HttpResponseError::ParseURL(ref err) => err.description(),
-            HttpResponseError::Io(ref err) => err.description(),
-            HttpResponseError::InvalidSocketAddress => "Invalid socket address",
-        }
+    /// Returns the body as text, decoded in the `charset` of the
+    /// `Content-Type` header, or as UTF-8 if there is none or it is unknown.
+    ///
+    /// Invalid sequences are replaced with U+FFFD.
+    pub fn text(&self) -> Cow<'_, str> {
+        charset::decode(&self.bytes, self.headers.get("Content-Type"))
     }
 
-    fn cause(&self) -> Option<&error::Error> {
-        match *self {
-            HttpResponseError::NotHttpScheme => Some(&HttpResponseError::NotHttpScheme),
-            HttpResponseError::ParseURL(ref err) => Some(err),
-            HttpResponseError::Io(ref err) => Some(err),
-            HttpResponseError::InvalidSocketAddress => {
-                Some(&HttpResponseError::InvalidSocketAddress)
-            }
+    /// Returns the body as text decoded in `charset`, e.g. `"windows-1252"`,
+    /// whatever the `Content-Type` header says. Unknown names fall back to
+    /// UTF-8.
+    pub fn text_with_charset(&self, charset: &str) -> Cow<'_, str> {
+        charset::decode_with_label(&self.bytes, charset)
+    }
+
+    /// Turns a 4xx or 5xx response into an `HttpResponseError::Status` error.
+    pub fn error_for_status(self) -> Result<HttpResponse, HttpResponseError> {
+        if self.status.is_client_error() || self.status.is_server_error() {
+            Err(HttpResponseError::Status(self.status))
+        } else {
+            Ok(self)
         }
     }
-}
 
-impl convert::From<url::ParseError> for HttpResponseError {
-    fn from(err: url::ParseError) -> HttpResponseError {
-        HttpResponseError::ParseURL(err)
+    /// Deserializes the body as JSON. Needs the `json` feature.
+    ///
+    /// The `Content-Type` is not checked.
+    #[cfg(feature = "json")]
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, HttpResponseError> {
+        serde_json::from_slice(&self.bytes).map_err(HttpResponseError::from)
+    }
+
+    /// Returns the URL this response came from, after any redirects.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Returns the language tags of every `Content-Language` header, in
+    /// order, e.g. `["en", "ja-JP"]`.
+    pub fn content_language(&self) -> Vec<&str> {
+        language::content_language(self.headers.get_all("Content-Language"))
+    }
+
+    /// Returns the protocol deviations tolerated while parsing this response.
+    ///
+    /// Always empty in `ParseMode::Strict`, which rejects them instead.
+    pub fn warnings(&self) -> &[ProtocolWarning] {
+        &self.warnings
+    }
+
+    /// Returns how many bytes were sent and received for this exchange.
+    pub fn transfer_sizes(&self) -> TransferSizes {
+        self.sizes
     }
-}
 
-impl convert::From<stdio::Error> for HttpResponseError {
-    fn from(err: stdio::Error) -> HttpResponseError {
-        HttpResponseError::Io(err)
+    /// Returns what was negotiated with the server if the response came over
+    /// TLS, after any redirects.
+    #[cfg(any(feature = "https", feature = "native-tls"))]
+    pub fn tls_info(&self) -> Option<&TlsInfo> {
+        self.tls.as_ref()
     }
 }
 
-const DEFAULT_HTTP_BUF_SIZE: usize = 8 * 1024;
+// Status line and headers of a response, before the body is read.
+pub(super) struct ResponseHead {
+    pub(super) status: StatusCode,
+    pub(super) reason: String,
+    pub(super) headers: HeaderMap,
+    pub(super) warnings: Vec<ProtocolWarning>,
+    #[cfg(any(feature = "https", feature = "native-tls"))]
+    pub(super) tls: Option<TlsInfo>,
+}
+
+// A response whose head has been read and whose body is still to come.
+struct OpenResponse {
+    url: Url,
+    head: ResponseHead,
+    body: PendingBody,
+    sizes: TransferSizes,
+}
+
+enum PendingBody {
+    // Synthesized locally, e.g. for a `data:` URL.
+    Data(Vec<u8>),
+    Wire(BodyReader),
+}
+
+// Time limits for one request, shared by every redirect it follows.
+#[derive(Clone, Copy)]
+struct Limits {
+    timeout: Option<Duration>,
+    deadline: Option<Instant>,
+}
+
+// Number of body pieces a streaming response reads ahead of its consumer.
+const STREAMED_CHUNKS: usize = 16;
 
 struct HttpStream {
-    inner: TcpStream,
+    inner: Transport,
     buffer: Box<[u8]>,
     position: usize,
     capacity: usize,
+    received: u64,
+    idle_timeout: Option<Duration>,
+    idle: Option<Delay>,
+    mode: ParseMode,
+    // Deviations tolerated so far in `ParseMode::Lenient`, each once.
+    warnings: Vec<ProtocolWarning>,
 }
 impl HttpStream {
-    fn new(inner: TcpStream) -> Self {
-        HttpStream::with_capacity(DEFAULT_HTTP_BUF_SIZE, inner)
-    }
-
-    fn with_capacity(capacity: usize, inner: TcpStream) -> Self {
-        unsafe {
-            let mut buffer = Vec::with_capacity(capacity);
-            buffer.set_len(capacity);
-            HttpStream {
-                inner,
-                buffer: buffer.into_boxed_slice(),
-                position: 0,
-                capacity: 0,
+    fn with_capacity(
+        capacity: usize,
+        inner: Transport,
+        idle_timeout: Option<Duration>,
+        mode: ParseMode,
+    ) -> Self {
+        HttpStream {
+            mode,
+            inner,
+            buffer: vec![0; capacity].into_boxed_slice(),
+            position: 0,
+            capacity: 0,
+            received: 0,
+            idle_timeout,
+            idle: None,
+            warnings: Vec::new(),
+        }
+    }
+
+    fn warn(&mut self, warning: ProtocolWarning) {
+        if !self.warnings.contains(&warning) {
+            self.warnings.push(warning);
+        }
+    }
+
+    // Turns a `WouldBlock` into `TimedOut` once the socket has made no
+    // progress for `idle_timeout`; any progress restarts the clock.
+    fn check_idle<T>(&mut self, result: Result<T, stdio::Error>) -> Result<T, stdio::Error> {
+        match result {
+            Err(ref err) if err.kind() == stdio::ErrorKind::WouldBlock => {
+                if let Some(timeout) = self.idle_timeout {
+                    let idle = self
+                        .idle
+                        .get_or_insert_with(|| Delay::new(Instant::now() + timeout));
+                    match idle.poll() {
+                        Ok(Async::Ready(())) => {
+                            return Err(stdio::Error::new(
+                                stdio::ErrorKind::TimedOut,
+                                "no progress within the request timeout",
+                            ))
+                        }
+                        Ok(Async::NotReady) => {}
+                        Err(err) => return Err(stdio::Error::other(err)),
+                    }
+                }
             }
+            _ => self.idle = None,
         }
+        result
     }
 }
 
 impl stdio::Read for HttpStream {
     fn read(&mut self, buffer: &mut [u8]) -> Result<usize, stdio::Error> {
         if self.position == self.capacity && buffer.len() >= self.buffer.len() {
-            return self.inner.read(buffer);
+            let result = self.inner.read(buffer);
+            let nread = self.check_idle(result)?;
+            self.received += nread as u64;
+            return Ok(nread);
         }
 
         let nread = {
@@ -161,209 +345,935 @@ impl stdio::Read for HttpStream {
 impl stdio::BufRead for HttpStream {
     fn fill_buf(&mut self) -> Result<&[u8], stdio::Error> {
         if self.position >= self.capacity {
-            self.capacity = self.inner.read(&mut self.buffer)?;
+            let result = self.inner.read(&mut self.buffer);
+            self.capacity = self.check_idle(result)?;
             self.position = 0;
         }
         Ok(&self.buffer[self.position..self.capacity])
     }
 
     fn consume(&mut self, amt: usize) {
-        self.position = cmp::min(self.position + amt, self.capacity);
+        let position = cmp::min(self.position + amt, self.capacity);
+        self.received += (position - self.position) as u64;
+        self.position = position;
     }
 }
 
 impl io::AsyncRead for HttpStream {}
 
-struct SimpleClient {}
+impl stdio::Write for HttpStream {
+    fn write(&mut self, buffer: &[u8]) -> Result<usize, stdio::Error> {
+        let result = self.inner.write(buffer);
+        self.check_idle(result)
+    }
+
+    fn flush(&mut self) -> Result<(), stdio::Error> {
+        let result = self.inner.flush();
+        self.check_idle(result)
+    }
+}
+
+impl io::AsyncWrite for HttpStream {
+    fn shutdown(&mut self) -> Poll<(), stdio::Error> {
+        io::AsyncWrite::shutdown(&mut self.inner)
+    }
+}
+
+/// A minimal blocking HTTP client.
+///
+/// Cloning is cheap; clones share their configuration.
+#[derive(Default, Clone)]
+pub struct SimpleClient {
+    pub(super) config: Arc<ClientConfig>,
+}
 
 impl SimpleClient {
-    fn new() -> Self {
-        SimpleClient {}
+    /// Creates a client with the default configuration.
+    pub fn new() -> Self {
+        SimpleClient::default()
+    }
+
+    /// Starts configuring a client.
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    /// Sends a `GET` request and returns the response headers and body.
+    pub fn get<U: IntoUrl>(&self, url: U) -> Result<HttpResponse, HttpResponseError> {
+        self.request(Method::GET, url).send()
+    }
+
+    /// Sends a `HEAD` request and returns the response headers.
+    ///
+    /// Any `Content-Length` in the response describes the body a `GET` would
+    /// have returned; no body is read.
+    pub fn head<U: IntoUrl>(&self, url: U) -> Result<HttpHeaders, HttpResponseError> {
+        self.request(Method::HEAD, url)
+            .send()
+            .map(|response| response.head)
+    }
+
+    /// Sends a `POST` request with `body` and returns the response headers and body.
+    pub fn post<U: IntoUrl, B: Into<Body>>(
+        &self,
+        url: U,
+        body: B,
+    ) -> Result<HttpResponse, HttpResponseError> {
+        self.request(Method::POST, url).body(body).send()
+    }
+
+    /// Sends a `PUT` request with `body`.
+    pub fn put<U: IntoUrl, B: Into<Body>>(
+        &self,
+        url: U,
+        body: B,
+    ) -> Result<HttpResponse, HttpResponseError> {
+        self.request(Method::PUT, url).body(body).send()
     }
 
-    fn get<S: Into<String>>(&self, url: S) -> Result<HttpResponse, HttpResponseError> {
-        let issue_list_url = Url::parse(&url.into())?;
-        if issue_list_url.scheme() != "http" {
-            return Err(HttpResponseError::NotHttpScheme);
+    /// Sends a `PATCH` request with `body`.
+    pub fn patch<U: IntoUrl, B: Into<Body>>(
+        &self,
+        url: U,
+        body: B,
+    ) -> Result<HttpResponse, HttpResponseError> {
+        self.request(Method::PATCH, url).body(body).send()
+    }
+
+    /// Sends a `DELETE` request.
+    pub fn delete<U: IntoUrl>(&self, url: U) -> Result<HttpResponse, HttpResponseError> {
+        self.request(Method::DELETE, url).send()
+    }
+
+    /// Sends an `OPTIONS` request.
+    pub fn options<U: IntoUrl>(&self, url: U) -> Result<HttpResponse, HttpResponseError> {
+        self.request(Method::OPTIONS, url).send()
+    }
+
+    /// Starts building a request with an arbitrary method.
+    ///
+    /// Use `"PROPFIND".parse::<Method>()` and the like for nonstandard verbs.
+    pub fn request<U: IntoUrl>(&self, method: Method, url: U) -> RequestBuilder {
+        RequestBuilder::new(self.clone(), method, url)
+    }
+
+    pub(super) fn execute(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<HttpResponse, HttpResponseError> {
+        let mut rt = Runtime::new()?;
+        let (response, deadline) = self.start(&mut rt, request)?;
+        let OpenResponse {
+            url,
+            mut head,
+            body,
+            mut sizes,
+        } = response;
+        let body = match body {
+            PendingBody::Data(data) => data,
+            PendingBody::Wire(reader) => {
+                let phase = Rc::new(Cell::new(Phase::ReadBody));
+                let (stream, body) = rt.block_on(within(reader.read_to_end(), deadline, phase))?;
+                sizes.body_bytes_received = stream.received - sizes.header_bytes_received;
+                for warning in stream.warnings {
+                    if !head.warnings.contains(&warning) {
+                        head.warnings.push(warning);
+                    }
+                }
+                body
+            }
+        };
+        Ok(HttpResponse::new(url, head, body, sizes))
+    }
+
+    // Reads the response head on the calling thread, then hands the
+    // connection to a thread of its own that feeds the body to the returned
+    // stream.
+    pub(super) fn execute_streaming(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<StreamingResponse, HttpResponseError> {
+        let (head_sender, head_receiver) = std_mpsc::channel();
+        let (sender, receiver) = mpsc::channel(STREAMED_CHUNKS);
+        let client = self.clone();
+        thread::Builder::new()
+            .name("glass-fi body".to_string())
+            .spawn(move || {
+                let started = Runtime::new()
+                    .map_err(HttpResponseError::from)
+                    .and_then(|mut rt| Ok((client.start(&mut rt, request)?, rt)));
+                let ((response, deadline), mut rt) = match started {
+                    Ok(started) => started,
+                    Err(err) => {
+                        let _ = head_sender.send(Err(err));
+                        return;
+                    }
+                };
+                let OpenResponse {
+                    url, head, body, ..
+                } = response;
+                if head_sender.send(Ok((url, head))).is_ok() {
+                    let _ = rt.block_on(forward_body(body, deadline, sender));
+                }
+            })?;
+        let (url, head) = head_receiver.recv().map_err(|_| {
+            HttpResponseError::from(stdio::Error::other("the connection thread exited"))
+        })??;
+        Ok(StreamingResponse::new(url, head, BodyStream::new(receiver)))
+    }
+
+    // Sends `request`, following redirects as configured, and returns the
+    // final response with its body still unread, along with the deadline.
+    fn start(
+        &self,
+        rt: &mut Runtime,
+        request: RequestBuilder,
+    ) -> Result<(OpenResponse, Option<Instant>), HttpResponseError> {
+        let RequestBuilder {
+            mut method,
+            url,
+            headers,
+            mut body,
+            timeout,
+            deadline,
+            ..
+        } = request;
+        let limits = Limits {
+            timeout: timeout.or(self.config.timeout),
+            deadline: deadline
+                .or(self.config.deadline)
+                .map(|budget| Instant::now() + budget),
+        };
+        let mut url = url?;
+        // Defaults are merged in once, so that following a redirect to
+        // another origin strips credentials set as defaults too.
+        let mut headers = self.with_defaults(&HeaderMap::from(headers));
+        let mut followed = 0;
+        loop {
+            let replay = body.as_ref().map(Body::try_clone);
+            let response = self.open(rt, &method, &url, &headers, body.take(), limits)?;
+            let limit = match self.config.redirect {
+                RedirectPolicy::None => return Ok((response, limits.deadline)),
+                RedirectPolicy::Limited(limit) => limit,
+            };
+            let next = match redirect::next_request(
+                &method,
+                &url,
+                response.head.status,
+                &response.head.headers,
+            ) {
+                Some(next) => next,
+                None => return Ok((response, limits.deadline)),
+            };
+            // A streamed body is gone once sent, so it cannot be repeated.
+            if next.keep_body && replay.as_ref().is_some_and(Option::is_none) {
+                return Ok((response, limits.deadline));
+            }
+            if followed == limit {
+                return Err(HttpResponseError::TooManyRedirects(next.url.into_string()));
+            }
+            redirect::strip_headers(&mut headers, &url, &next.url, next.keep_body);
+            if let Some(ref hook) = self.config.redirect_hook {
+                let mut attempt = RedirectAttempt {
+                    status: response.head.status,
+                    response_headers: &response.head.headers,
+                    previous: &url,
+                    url: &next.url,
+                    method: &next.method,
+                    headers: &mut headers,
+                    followed,
+                };
+                match hook.on_redirect(&mut attempt) {
+                    RedirectAction::Follow => {}
+                    RedirectAction::Stop => return Ok((response, limits.deadline)),
+                    RedirectAction::Deny => {
+                        return Err(HttpResponseError::RedirectDenied(next.url.into_string()))
+                    }
+                }
+                for (name, value) in &headers {
+                    validate_header(name.to_string(), value.to_string())?;
+                }
+            }
+            followed += 1;
+            if next.keep_body {
+                body = replay.flatten();
+            }
+            method = next.method;
+            url = next.url;
         }
-        if let Ok(mut socket_addrs) = issue_list_url.to_socket_addrs() {
-            let socket_addr = socket_addrs.next().unwrap();
-            let connect_future = TcpStream::connect(&socket_addr);
-            let headers = Arc::new(Mutex::new(Vec::new()));
-            let content = Arc::new(Mutex::new(String::new()));
-            {
-                let content = content.clone();
-                let headers = headers.clone();
-                let task = connect_future
-                    .and_then(move |mut socket| {
-                        let buffer = "GET / HTTP/2.0\nHost: localhost\nConnection: keep-alive\n\n"
-                            .as_bytes();
-                        loop {
-                            match socket.poll_write(buffer) {
-                                Ok(Async::Ready(_)) => break,
-                                Err(err) => eprintln!("Error: {:?}", err),
-                                _ => {}
-                            }
+    }
 
-                            let milli = time::Duration::from_millis(1);
-                            let now = time::Instant::now();
-                            thread::sleep(milli);
-                        }
+    // Sends a single request, without following redirects, and reads the
+    // response head.
+    fn open(
+        &self,
+        rt: &mut Runtime,
+        method: &Method,
+        url: &Url,
+        headers: &HeaderMap,
+        body: Option<Body>,
+        limits: Limits,
+    ) -> Result<OpenResponse, HttpResponseError> {
+        let Limits { timeout, deadline } = limits;
+        match url.scheme() {
+            "http" => {}
+            #[cfg(any(feature = "https", feature = "native-tls"))]
+            "https" => {}
+            "data" => return data_response(url),
+            _ => return Err(HttpResponseError::NotHttpScheme),
+        }
+        let method = method.clone();
+        let socket_addr = url
+            .to_socket_addrs()
+            .ok()
+            .and_then(|mut socket_addrs| socket_addrs.next())
+            .ok_or(HttpResponseError::InvalidSocketAddress)?;
+        // Resolution blocks and cannot be interrupted, so the budget is only
+        // checked once it returns.
+        if deadline.is_some_and(|deadline| deadline <= Instant::now()) {
+            return Err(HttpResponseError::DeadlineExceeded(Phase::Resolve));
+        }
+        let headers = self.outgoing_headers(url, headers);
+        let mut request = h1::encode_request_head(
+            &method,
+            &request_target(url),
+            &headers,
+            body.as_ref().map(Body::framing),
+            self.config.preserve_header_order,
+        );
+        let header_bytes_sent = request.len() as u64;
+        let mut streamed = None;
+        match body.map(|body| body.kind) {
+            Some(BodyKind::Bytes(bytes)) => request.extend_from_slice(&bytes),
+            Some(BodyKind::Streamed(body)) => streamed = Some(body),
+            None => {}
+        }
+        let read_buffer_size = self.config.read_buffer_size;
+        let mode = self.config.parse_mode;
+        let response_url = url.clone();
 
-                        let content = content.clone();
-                        let headers = headers.clone();
-                        let mut in_http_header = false;
-                        let mut http_content_remain: i64 = 0;
-                        let http_stream = HttpStream::new(socket);
-                        let read_to_end_task = io::lines(http_stream)
-                            .map_err(|err| eprintln!("Error: {:?}", err))
-                            .for_each(move |input| {
-                                eprintln!("Read :{}", input);
-                                if !in_http_header && http_content_remain > 0 {
-                                    http_content_remain -= input.len() as i64 + 1;
-                                    let mut content = content.lock().unwrap();
-                                    *content = format!("{}{}\n", *content, input);
-                                    if http_content_remain <= 0 {
-                                        (*content).pop().unwrap();
-                                        return Err(());
-                                    }
-                                    return Ok(());
-                                }
-                                if let Some(_) = input.find("HTTP") {
-                                    in_http_header = true;
-                                    return Ok(());
-                                }
-                                match input {
-                                    ref x if x.trim().is_empty() => {
-                                        in_http_header = false;
-                                        Ok(())
-                                    }
-                                    header_content => {
-                                        let mut header_content = header_content.splitn(2, ':');
-                                        let (name, content) = (
-                                            header_content.next().unwrap(),
-                                            header_content.next().unwrap(),
-                                        );
-                                        let mut headers = headers.lock().unwrap();
-                                        (*headers).push(HttpHeader {
-                                            name: name.trim().to_string(),
-                                            content: content.trim().to_string(),
-                                        });
-                                        if let Some(num) = name.trim().find("Content-Length") {
-                                            if num == 0 {
-                                                http_content_remain =
-                                                    content.trim().parse::<_>().unwrap();
-                                                eprintln!(
-                                                    "Content remain: {:?}",
-                                                    &http_content_remain
-                                                );
-                                            }
-                                        }
-                                        Ok(())
-                                    }
-                                }
-                            })
-                            .map_err(|err| eprintln!("Error: {:?}", err));
-                        let mut http_runtime = Runtime::new().unwrap();
-                        http_runtime.spawn(read_to_end_task);
-                        http_runtime.shutdown_now().wait().unwrap();
-                        Ok(())
-                    })
-                    .map_err(|err| eprintln!("Error: {:?}", err));
-                let mut rt = Runtime::new().unwrap();
-                rt.spawn(task);
-                rt.shutdown_on_idle().wait().unwrap();
+        let connect = self.connect(url, &socket_addr);
+        let connect: Box<dyn Future<Item = Transport, Error = HttpResponseError>> = match timeout {
+            Some(timeout) => Box::new(Timeout::new(connect, timeout).map_err(|err| {
+                if err.is_elapsed() {
+                    HttpResponseError::from(stdio::Error::new(
+                        stdio::ErrorKind::TimedOut,
+                        ConnectTimedOut,
+                    ))
+                } else if err.is_inner() {
+                    err.into_inner().unwrap()
+                } else {
+                    HttpResponseError::from(stdio::Error::other(err))
+                }
+            })),
+            None => connect,
+        };
+        let phase = Rc::new(Cell::new(Phase::Connect));
+        let reached = phase.clone();
+        let task = connect
+            .and_then(move |socket| {
+                reached.set(Phase::SendRequest);
+                io::write_all(
+                    HttpStream::with_capacity(read_buffer_size, socket, timeout, mode),
+                    request,
+                )
+                .map_err(HttpResponseError::from)
+                .and_then(move |(stream, request)| {
+                    let fixed = request.len() as u64 - header_bytes_sent;
+                    send_body(stream, streamed).map(move |(stream, sent)| (stream, fixed + sent))
+                })
+                .map(move |sent| (sent, reached))
+            })
+            .and_then(|((stream, body_bytes_sent), reached)| {
+                reached.set(Phase::ReadHead);
+                read_final_head(stream).map(move |(stream, head)| (stream, head, body_bytes_sent))
+            })
+            .and_then(move |(mut stream, mut head, body_bytes_sent)| {
+                head.warnings = stream.warnings.split_off(0);
+                let framing = h1::response_body_framing(
+                    &method,
+                    head.status,
+                    &head.headers,
+                    stream.mode,
+                    &mut head.warnings,
+                )?;
+                #[cfg(any(feature = "https", feature = "native-tls"))]
+                {
+                    head.tls = stream.inner.tls_info().cloned();
+                }
+                let sizes = TransferSizes {
+                    header_bytes_sent,
+                    body_bytes_sent,
+                    header_bytes_received: stream.received,
+                    body_bytes_received: 0,
+                };
+                Ok(OpenResponse {
+                    url: response_url,
+                    head,
+                    body: PendingBody::Wire(BodyReader::new(stream, framing)),
+                    sizes,
+                })
+            });
+        rt.block_on(within(task, deadline, phase))
+    }
+
+    // Opens the connection for `url`, with the TLS handshake for `https`.
+    fn connect(
+        &self,
+        url: &Url,
+        addr: &SocketAddr,
+    ) -> Box<dyn Future<Item = Transport, Error = HttpResponseError>> {
+        let tcp = TcpStream::connect(addr).map_err(HttpResponseError::from);
+        match url.scheme() {
+            #[cfg(any(feature = "https", feature = "native-tls"))]
+            "https" => {
+                let config = match self.config.tls {
+                    Some(ref config) => config.clone(),
+                    None => match tls::default_connector() {
+                        Ok(config) => config,
+                        Err(err) => return Box::new(future::err(err)),
+                    },
+                };
+                let host = url.host_str().unwrap_or("").to_string();
+                Box::new(tcp.and_then(move |socket| config.connect(&host, socket)))
+            }
+            _ => Box::new(tcp.map(Transport::Plain)),
+        }
+    }
+
+    // The client's defaults followed by `request`, whose headers override
+    // defaults of the same name.
+    fn with_defaults(&self, request: &HeaderMap) -> HeaderMap {
+        let mut merged = HeaderMap::new();
+        for (name, value) in &self.config.default_headers {
+            if !request.contains_key(name) {
+                merged.append(name, value);
             }
-            let content = content.lock().unwrap();
-            eprintln!("Content:\n{:}", content);
-            let headers = headers.lock().unwrap();
-            Ok(HttpResponse::new(
-                HttpHeaders {
-                    inner: (*headers).clone(),
-                },
-                (*content).clone(),
-            ))
+        }
+        for (name, value) in request {
+            merged.append(name, value);
+        }
+        merged
+    }
+
+    // `Host` and `Connection` are filled in unless `request`, which already
+    // holds the defaults, sets them: first, or after every other header if
+    // the order is preserved. Any profile has the last word.
+    fn outgoing_headers(&self, url: &Url, request: &HeaderMap) -> HeaderMap {
+        let mut implicit = HeaderMap::new();
+        if !request.contains_key("Host") {
+            implicit.append("Host", host_header(url));
+        }
+        if !request.contains_key("Connection") {
+            implicit.append("Connection", "keep-alive");
+        }
+        let (first, last) = if self.config.preserve_header_order {
+            (request, &implicit)
         } else {
-            Err(HttpResponseError::InvalidSocketAddress)
+            (&implicit, request)
+        };
+        let mut outgoing = HeaderMap::new();
+        for (name, value) in first.iter().chain(last) {
+            outgoing.append(name, value);
         }
+        if let Some(ref profile) = self.config.profile {
+            profile.apply(&mut outgoing);
+        }
+        outgoing
+    }
+}
+
+// Answers a `data:` URL locally with a synthesized response.
+fn data_response(url: &Url) -> Result<OpenResponse, HttpResponseError> {
+    let (media_type, data) = data_url::decode(url)?;
+    let status = StatusCode::from_u16(200)?;
+    let head = ResponseHead {
+        status,
+        reason: status.canonical_reason().unwrap_or("").to_string(),
+        headers: HeaderMap::from(vec![
+            HttpHeader {
+                name: "Content-Type".to_string(),
+                content: media_type,
+            },
+            HttpHeader {
+                name: "Content-Length".to_string(),
+                content: data.len().to_string(),
+            },
+        ]),
+        warnings: Vec::new(),
+        #[cfg(any(feature = "https", feature = "native-tls"))]
+        tls: None,
+    };
+    Ok(OpenResponse {
+        url: url.clone(),
+        head,
+        body: PendingBody::Data(data),
+        sizes: TransferSizes::default(),
+    })
+}
+
+// Runs `task` against `deadline`, reporting the phase it was in if the
+// deadline passes first.
+fn within<F>(
+    task: F,
+    deadline: Option<Instant>,
+    phase: Rc<Cell<Phase>>,
+) -> Box<dyn Future<Item = F::Item, Error = HttpResponseError>>
+where
+    F: Future<Error = HttpResponseError> + 'static,
+{
+    match deadline {
+        Some(deadline) => Box::new(Timeout::new_at(task, deadline).map_err(move |err| {
+            if err.is_elapsed() {
+                HttpResponseError::DeadlineExceeded(phase.get())
+            } else if err.is_inner() {
+                err.into_inner().unwrap()
+            } else {
+                HttpResponseError::from(stdio::Error::other(err))
+            }
+        })),
+        None => Box::new(task),
+    }
+}
+
+// Origin-form target: the path and query of `url`, never the fragment. `Url`
+// has already percent-encoded both.
+fn request_target(url: &Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    }
+}
+
+// `Host` value for `url`: the host, bracketed if it is an IPv6 literal, and
+// the port unless it is the scheme's default.
+fn host_header(url: &Url) -> String {
+    let host = match url.host() {
+        Some(Host::Ipv6(addr)) => format!("[{}]", addr),
+        Some(host) => host.to_string(),
+        None => String::new(),
+    };
+    match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host,
     }
+}
 
-    fn head<S: Into<String>>(&self, url: S) -> Result<HttpHeaders, HttpResponseError> {
-        let issue_list_url = Url::parse(&url.into())?;
-        if issue_list_url.scheme() != "http" {
-            return Err(HttpResponseError::NotHttpScheme);
+fn read_line(
+    stream: HttpStream,
+) -> impl Future<Item = (HttpStream, String), Error = HttpResponseError> {
+    io::read_until(stream, b'\n', Vec::new())
+        .map_err(HttpResponseError::from)
+        .and_then(|(mut stream, mut line)| {
+            if line.pop() != Some(b'\n') {
+                return Err(HttpResponseError::from(stdio::Error::new(
+                    stdio::ErrorKind::UnexpectedEof,
+                    "connection closed before the response head was complete",
+                )));
+            }
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            } else if stream.mode == ParseMode::Strict {
+                return Err(HttpResponseError::ProtocolViolation(format!(
+                    "bare LF after {:?}",
+                    String::from_utf8_lossy(&line)
+                )));
+            } else {
+                stream.warn(ProtocolWarning::BareLineFeed);
+            }
+            String::from_utf8(line)
+                .map(|line| (stream, line))
+                .map_err(|err| {
+                    HttpResponseError::InvalidHeader(
+                        String::from_utf8_lossy(err.as_bytes()).into_owned(),
+                    )
+                })
+        })
+}
+
+fn read_head(
+    stream: HttpStream,
+) -> impl Future<Item = (HttpStream, ResponseHead), Error = HttpResponseError> {
+    read_line(stream)
+        .and_then(|(stream, status_line)| {
+            let (status, reason) = status::parse_status_line(&status_line)?;
+            Ok((stream, status, reason))
+        })
+        .and_then(|(stream, status, reason)| {
+            future::loop_fn((stream, Vec::new()), move |(stream, mut headers)| {
+                let reason = reason.clone();
+                read_line(stream).and_then(move |(mut stream, line)| {
+                    if line.is_empty() {
+                        let head = ResponseHead {
+                            status,
+                            reason,
+                            headers: HeaderMap::from(headers),
+                            warnings: Vec::new(),
+                            #[cfg(any(feature = "https", feature = "native-tls"))]
+                            tls: None,
+                        };
+                        return Ok(future::Loop::Break((stream, head)));
+                    }
+                    headers.push(parse_header(line, &mut stream)?);
+                    Ok(future::Loop::Continue((stream, headers)))
+                })
+            })
+        })
+}
+
+// Reads response heads until one that is not interim, such as 100 Continue
+// or 102 Processing, which are dropped.
+fn read_final_head(
+    stream: HttpStream,
+) -> impl Future<Item = (HttpStream, ResponseHead), Error = HttpResponseError> {
+    future::loop_fn(stream, |stream| {
+        read_head(stream).map(|(stream, head)| {
+            if head.status.is_interim() {
+                future::Loop::Continue(stream)
+            } else {
+                future::Loop::Break((stream, head))
+            }
+        })
+    })
+}
+
+fn parse_header(line: String, stream: &mut HttpStream) -> Result<HttpHeader, HttpResponseError> {
+    let (raw_name, content) = match line.find(':') {
+        Some(colon) => (&line[..colon], line[colon + 1..].trim()),
+        None => return Err(HttpResponseError::InvalidHeader(line)),
+    };
+    let name = raw_name.trim();
+    if name.is_empty() {
+        return Err(HttpResponseError::InvalidHeader(line.clone()));
+    }
+    if raw_name.contains(char::is_whitespace) {
+        if stream.mode == ParseMode::Strict {
+            return Err(HttpResponseError::ProtocolViolation(format!(
+                "whitespace in header name {:?}",
+                raw_name
+            )));
         }
-        if let Ok(mut socket_addrs) = issue_list_url.to_socket_addrs() {
-            let socket_addr = socket_addrs.next().unwrap();
-            let inner = Arc::new(Mutex::new(Vec::new()));
-            let connect_future = TcpStream::connect(&socket_addr);
-            {
-                let inner = inner.clone();
-                let task = connect_future
-                    .and_then(move |mut socket| {
-                        let buffer = "HEAD / HTTP/2.0\nHost: localhost\nConnection: keep-alive\n\n"
-                            .as_bytes();
-                        loop {
-                            match socket.poll_write(buffer) {
-                                Ok(Async::Ready(_)) => break,
-                                Err(err) => eprintln!("Error: {:?}", err),
-                                _ => {}
+        stream.warn(ProtocolWarning::WhitespaceInHeaderName);
+    }
+    Ok(HttpHeader {
+        name: name.to_string(),
+        content: content.to_string(),
+    })
+}
+
+// Writes a streamed body as `source` yields it, framed by `Content-Length`
+// if its length is known and chunked otherwise, and returns the number of
+// bytes written.
+fn send_body(
+    stream: HttpStream,
+    streamed: Option<Streamed>,
+) -> Box<dyn Future<Item = (HttpStream, u64), Error = HttpResponseError>> {
+    let Streamed {
+        source,
+        length,
+        trailers,
+    } = match streamed {
+        Some(streamed) => streamed,
+        None => return Box::new(future::ok((stream, 0))),
+    };
+    let pieces = future::loop_fn(
+        (stream, source, 0),
+        move |(stream, source, sent)| -> Box<dyn Future<Item = _, Error = _>> {
+            if length == Some(sent) {
+                return Box::new(future::ok(future::Loop::Break((stream, sent))));
+            }
+            Box::new(
+                source
+                    .into_future()
+                    .map_err(|(err, _)| HttpResponseError::from(err))
+                    .and_then(move |(chunk, source)| {
+                        let data = match (chunk, length) {
+                            (Some(chunk), Some(length)) => {
+                                let left = cmp::min(length - sent, chunk.len() as u64);
+                                chunk[..left as usize].to_vec()
+                            }
+                            (Some(chunk), None) if chunk.is_empty() => Vec::new(),
+                            (Some(chunk), None) => h1::encode_chunk(&chunk),
+                            (None, Some(_)) => {
+                                return Err(HttpResponseError::from(stdio::Error::new(
+                                    stdio::ErrorKind::UnexpectedEof,
+                                    "request body ended before its Content-Length",
+                                )))
                             }
+                            (None, None) => return Ok(None),
+                        };
+                        Ok(Some((data, source)))
+                    })
+                    .and_then(
+                        move |piece| -> Box<dyn Future<Item = _, Error = HttpResponseError>> {
+                            match piece {
+                                Some((data, source)) => Box::new(
+                                    io::write_all(stream, data)
+                                        .map_err(HttpResponseError::from)
+                                        .map(move |(stream, data)| {
+                                            let sent = sent + data.len() as u64;
+                                            future::Loop::Continue((stream, source, sent))
+                                        }),
+                                ),
+                                None => Box::new(future::ok(future::Loop::Break((stream, sent)))),
+                            }
+                        },
+                    ),
+            )
+        },
+    );
+    if length.is_some() {
+        return Box::new(pieces);
+    }
+    Box::new(pieces.and_then(move |(stream, sent)| {
+        let last: Box<dyn Future<Item = _, Error = _>> = match trailers {
+            Some(trailers) => Box::new(
+                trailers
+                    .map_err(HttpResponseError::from)
+                    .and_then(|trailers| check_trailers(&trailers).map(|()| trailers))
+                    .map(|trailers| h1::encode_last_chunk(&trailers)),
+            ),
+            None => Box::new(future::ok(h1::LAST_CHUNK.to_vec())),
+        };
+        last.and_then(move |last| {
+            io::write_all(stream, last)
+                .map_err(HttpResponseError::from)
+                .map(move |(stream, last)| (stream, sent + last.len() as u64))
+        })
+    }))
+}
 
-                            let milli = time::Duration::from_millis(1);
-                            let now = time::Instant::now();
-                            thread::sleep(milli)
-                        }
+// Trailers must be valid header fields, and not ones that frame or route
+// the message.
+fn check_trailers(trailers: &HeaderMap) -> Result<(), HttpResponseError> {
+    for (name, value) in trailers {
+        validate_header(name.to_string(), value.to_string())?;
+        if ["Content-Length", "Transfer-Encoding", "Trailer", "Host"]
+            .iter()
+            .any(|forbidden| name.eq_ignore_ascii_case(forbidden))
+        {
+            return Err(HttpResponseError::InvalidHeader(format!(
+                "{}: not allowed as a trailer",
+                name
+            )));
+        }
+    }
+    Ok(())
+}
 
-                        let inner = inner.clone();
-                        let mut in_http_header = false;
-                        let mut http_content_remain: i64 = 0;
-                        let http_stream = HttpStream::new(socket);
-                        let lines_task = io::lines(http_stream)
-                            .map_err(|err| eprintln!("Error: {:?}", err))
-                            .for_each(move |input| {
-                                eprintln!("Read : {}", input);
-                                if let Some(_) = input.find("HTTP") {
-                                    in_http_header = true;
-                                    return Ok(());
-                                }
-                                match input {
-                                    ref x if x.trim().is_empty() => Err(()),
-                                    header_content => {
-                                        let mut header_content = header_content.splitn(2, ':');
-                                        let (name, content) = (
-                                            header_content.next().unwrap(),
-                                            header_content.next().unwrap(),
-                                        );
-                                        let mut inner = inner.lock().unwrap();
-                                        (*inner).push(HttpHeader {
-                                            name: name.trim().to_string(),
-                                            content: content.trim().to_string(),
-                                        });
-                                        Ok(())
-                                    }
-                                }
-                            })
-                            .map_err(|err| eprintln!("Error: {:?}", err));
-                        let mut http_runtime = Runtime::new().unwrap();
-                        http_runtime.spawn(lines_task);
-                        http_runtime.shutdown_now().wait().unwrap();
-                        Ok(())
-                    })
-                    .map_err(|err| eprintln!("Error: {:?}", err));
-                let mut rt = Runtime::new().unwrap();
-                rt.spawn(task);
-                rt.shutdown_on_idle().wait().unwrap();
+// Where a `BodyReader` is within the body.
+#[derive(Clone, Copy)]
+enum BodyState {
+    // This many bytes of a `Content-Length` body are left.
+    Length(u64),
+    // A chunk-size line is next.
+    ChunkSize,
+    // This many bytes of the current chunk are left.
+    Chunk(u64),
+    // The line break after a chunk's data is next.
+    ChunkEnd,
+    // The body runs until the connection closes.
+    UntilClose,
+    Done,
+}
+
+// Reads a response body off the connection a piece at a time, so that it can
+// either be collected or handed out as it arrives.
+struct BodyReader {
+    stream: HttpStream,
+    state: BodyState,
+}
+
+// A reader and the next piece of the body, or `None` once it is complete.
+type NextChunk = (BodyReader, Option<Vec<u8>>);
+
+impl BodyReader {
+    fn new(stream: HttpStream, framing: BodyFraming) -> Self {
+        let state = match framing {
+            BodyFraming::Empty => BodyState::Done,
+            BodyFraming::Length(length) => BodyState::Length(length),
+            BodyFraming::Chunked => BodyState::ChunkSize,
+            BodyFraming::CloseDelimited => BodyState::UntilClose,
+        };
+        BodyReader { stream, state }
+    }
+
+    // Reads the next piece of the body, at most one read buffer long.
+    fn next_chunk(self) -> impl Future<Item = NextChunk, Error = HttpResponseError> {
+        future::loop_fn(self, BodyReader::step)
+    }
+
+    fn read_to_end(self) -> impl Future<Item = (HttpStream, Vec<u8>), Error = HttpResponseError> {
+        future::loop_fn((self, Vec::new()), |(reader, mut body)| {
+            reader.next_chunk().map(|(reader, chunk)| match chunk {
+                Some(chunk) => {
+                    body.extend_from_slice(&chunk);
+                    future::Loop::Continue((reader, body))
+                }
+                None => future::Loop::Break((reader.stream, body)),
+            })
+        })
+    }
+
+    // Advances by one read or one line. Chunk framing is consumed without
+    // producing a piece.
+    fn step(
+        self,
+    ) -> Box<dyn Future<Item = future::Loop<NextChunk, BodyReader>, Error = HttpResponseError>>
+    {
+        let BodyReader { stream, state } = self;
+        match state {
+            BodyState::Done | BodyState::Length(0) => {
+                let reader = BodyReader {
+                    stream,
+                    state: BodyState::Done,
+                };
+                Box::new(future::ok(future::Loop::Break((reader, None))))
             }
-            let inner = inner.lock().unwrap();
-            let inner = (*inner).clone();
-            Ok(HttpHeaders { inner })
-        } else {
-            Err(HttpResponseError::InvalidSocketAddress)
+            BodyState::Length(left) => {
+                Box::new(read_some(stream, left).and_then(move |(stream, data)| {
+                    let state = BodyState::Length(left - data.len() as u64);
+                    Ok(future::Loop::Break((
+                        BodyReader { stream, state },
+                        Some(non_empty(data)?),
+                    )))
+                }))
+            }
+            BodyState::ChunkSize => Box::new(
+                read_line(stream)
+                    .and_then(|(stream, line)| Ok((stream, h1::parse_chunk_size(&line)?)))
+                    .and_then(|(stream, size)| -> Box<dyn Future<Item = _, Error = _>> {
+                        if size == 0 {
+                            return Box::new(skip_trailers(stream).map(|stream| {
+                                future::Loop::Continue(BodyReader {
+                                    stream,
+                                    state: BodyState::Done,
+                                })
+                            }));
+                        }
+                        Box::new(future::ok(future::Loop::Continue(BodyReader {
+                            stream,
+                            state: BodyState::Chunk(size),
+                        })))
+                    }),
+            ),
+            BodyState::Chunk(left) => {
+                Box::new(read_some(stream, left).and_then(move |(stream, data)| {
+                    let state = match left - data.len() as u64 {
+                        0 => BodyState::ChunkEnd,
+                        left => BodyState::Chunk(left),
+                    };
+                    Ok(future::Loop::Break((
+                        BodyReader { stream, state },
+                        Some(non_empty(data)?),
+                    )))
+                }))
+            }
+            BodyState::ChunkEnd => Box::new(read_line(stream).and_then(|(stream, line)| {
+                if !line.is_empty() {
+                    return Err(HttpResponseError::InvalidChunk(line));
+                }
+                Ok(future::Loop::Continue(BodyReader {
+                    stream,
+                    state: BodyState::ChunkSize,
+                }))
+            })),
+            BodyState::UntilClose => Box::new(read_some(stream, u64::MAX).map(|(stream, data)| {
+                if data.is_empty() {
+                    let reader = BodyReader {
+                        stream,
+                        state: BodyState::Done,
+                    };
+                    return future::Loop::Break((reader, None));
+                }
+                let reader = BodyReader {
+                    stream,
+                    state: BodyState::UntilClose,
+                };
+                future::Loop::Break((reader, Some(data)))
+            })),
         }
     }
 }
 
+// Reads at most `limit` bytes, and at most one read buffer. Nothing is read
+// only once the connection has been closed.
+fn read_some(
+    stream: HttpStream,
+    limit: u64,
+) -> impl Future<Item = (HttpStream, Vec<u8>), Error = HttpResponseError> {
+    let len = cmp::min(limit, stream.buffer.len() as u64) as usize;
+    io::read(stream, vec![0; len])
+        .map(|(stream, mut data, nread)| {
+            data.truncate(nread);
+            (stream, data)
+        })
+        .map_err(HttpResponseError::from)
+}
+
+// Fails on the empty read that means the connection closed mid-body.
+fn non_empty(data: Vec<u8>) -> Result<Vec<u8>, HttpResponseError> {
+    if data.is_empty() {
+        return Err(HttpResponseError::from(stdio::Error::new(
+            stdio::ErrorKind::UnexpectedEof,
+            "connection closed before the response body was complete",
+        )));
+    }
+    Ok(data)
+}
+
+// Feeds `body` to `sender` piece by piece, ending with the error if reading
+// it fails. Stops early once the receiving end is dropped.
+fn forward_body(
+    body: PendingBody,
+    deadline: Option<Instant>,
+    sender: mpsc::Sender<Result<Bytes, HttpResponseError>>,
+) -> Box<dyn Future<Item = (), Error = ()>> {
+    let reader = match body {
+        PendingBody::Data(ref data) if data.is_empty() => return Box::new(future::ok(())),
+        PendingBody::Data(data) => {
+            return Box::new(
+                sender
+                    .send(Ok(Bytes::from(data)))
+                    .map(|_| ())
+                    .map_err(|_| ()),
+            )
+        }
+        PendingBody::Wire(reader) => reader,
+    };
+    Box::new(future::loop_fn(
+        (reader, sender),
+        move |(reader, sender)| {
+            let phase = Rc::new(Cell::new(Phase::ReadBody));
+            within(reader.next_chunk(), deadline, phase).then(move |result| {
+                let (reader, item) = match result {
+                    Ok((_, None)) => return future::Either::A(future::ok(future::Loop::Break(()))),
+                    Ok((reader, Some(chunk))) => (Some(reader), Ok(Bytes::from(chunk))),
+                    Err(err) => (None, Err(err)),
+                };
+                future::Either::B(sender.send(item).map_err(|_| ()).map(
+                    move |sender| match reader {
+                        Some(reader) => future::Loop::Continue((reader, sender)),
+                        None => future::Loop::Break(()),
+                    },
+                ))
+            })
+        },
+    ))
+}
+
+fn skip_trailers(stream: HttpStream) -> impl Future<Item = HttpStream, Error = HttpResponseError> {
+    future::loop_fn(stream, |stream| {
+        read_line(stream).map(|(stream, line)| {
+            if line.is_empty() {
+                future::Loop::Break(stream)
+            } else {
+                future::Loop::Continue(stream)
+            }
+        })
+    })
+}
+
 #[test]
 fn simple_get_http() {
     let client = SimpleClient::new();
@@ -395,3 +1305,15 @@ fn get_headers_by_get_request() {
     let server_name = headers.find(|x| x.name == "Server").unwrap().content;
     assert_eq!("nginx/1.10.3 (Ubuntu)", server_name);
 }
+
+#[test]
+fn host_header_from_url() {
+    let host = |url: &str| host_header(&Url::parse(url).unwrap());
+    assert_eq!("example.com", host("http://example.com/"));
+    assert_eq!("example.com", host("http://Example.COM:80/a"));
+    assert_eq!("example.com:8080", host("http://example.com:8080/"));
+    assert_eq!("127.0.0.1:81", host("http://127.0.0.1:81/"));
+    assert_eq!("[::1]", host("http://[::1]/"));
+    assert_eq!("[2001:db8::1]:8080", host("http://[2001:db8::1]:8080/"));
+    assert_eq!("xn--caf-dma.example", host("http://café.example/"));
+}
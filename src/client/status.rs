@@ -0,0 +1,242 @@
+#![deny(missing_docs)]
+
+use std::fmt;
+
+use super::HttpResponseError;
+
+/// An HTTP status code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StatusCode(u16);
+
+/// The class of a status code, given by its first digit.
+///
+/// Clients treat a code they do not know like the `x00` code of its class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatusClass {
+    /// `1xx`: an interim response; the final one follows.
+    Informational,
+    /// `2xx`: the request was received, understood and accepted.
+    Success,
+    /// `3xx`: further action is needed to complete the request.
+    Redirection,
+    /// `4xx`: the request is in error.
+    ClientError,
+    /// `5xx`: the server failed to fulfill a valid request.
+    ServerError,
+}
+
+impl StatusCode {
+    /// Creates a status code from its numeric value, which must be in `100..=999`.
+    pub fn from_u16(code: u16) -> Result<StatusCode, HttpResponseError> {
+        if (100..1000).contains(&code) {
+            Ok(StatusCode(code))
+        } else {
+            Err(HttpResponseError::InvalidStatusLine(code.to_string()))
+        }
+    }
+
+    /// Returns the numeric value.
+    pub fn as_u16(self) -> u16 {
+        self.0
+    }
+
+    /// Returns the class, or `None` for codes from 600 up, which have none.
+    pub fn class(self) -> Option<StatusClass> {
+        match self.0 / 100 {
+            1 => Some(StatusClass::Informational),
+            2 => Some(StatusClass::Success),
+            3 => Some(StatusClass::Redirection),
+            4 => Some(StatusClass::ClientError),
+            5 => Some(StatusClass::ServerError),
+            _ => None,
+        }
+    }
+
+    /// `1xx`
+    pub fn is_informational(self) -> bool {
+        self.0 / 100 == 1
+    }
+
+    /// `2xx`
+    pub fn is_success(self) -> bool {
+        self.0 / 100 == 2
+    }
+
+    /// `3xx`
+    pub fn is_redirection(self) -> bool {
+        self.0 / 100 == 3
+    }
+
+    /// `4xx`
+    pub fn is_client_error(self) -> bool {
+        self.0 / 100 == 4
+    }
+
+    /// `5xx`
+    pub fn is_server_error(self) -> bool {
+        self.0 / 100 == 5
+    }
+
+    /// Returns `true` for interim responses, which the client skips while
+    /// waiting for the final one: every `1xx` code except 101 Switching
+    /// Protocols, e.g. 100 Continue, 102 Processing and 103 Early Hints.
+    pub fn is_interim(self) -> bool {
+        self.is_informational() && self.0 != 101
+    }
+
+    /// Returns `true` if responses with this code never have content,
+    /// whatever their headers say: `1xx`, 204 No Content, 205 Reset Content
+    /// and 304 Not Modified.
+    pub fn forbids_content(self) -> bool {
+        self.is_informational() || matches!(self.0, 204 | 205 | 304)
+    }
+
+    /// Returns `true` for 226 IM Used: the body is the result of the
+    /// instance manipulations named in the `IM` header, such as a delta to
+    /// apply to a cached copy (RFC 3229), not the resource itself.
+    pub fn is_instance_manipulated(self) -> bool {
+        self.0 == 226
+    }
+
+    /// Returns the reason phrase registered for this code, if it has one.
+    pub fn canonical_reason(self) -> Option<&'static str> {
+        let reason = match self.0 {
+            100 => "Continue",
+            101 => "Switching Protocols",
+            102 => "Processing",
+            103 => "Early Hints",
+            200 => "OK",
+            201 => "Created",
+            202 => "Accepted",
+            203 => "Non-Authoritative Information",
+            204 => "No Content",
+            205 => "Reset Content",
+            206 => "Partial Content",
+            207 => "Multi-Status",
+            208 => "Already Reported",
+            226 => "IM Used",
+            300 => "Multiple Choices",
+            301 => "Moved Permanently",
+            302 => "Found",
+            303 => "See Other",
+            304 => "Not Modified",
+            305 => "Use Proxy",
+            307 => "Temporary Redirect",
+            308 => "Permanent Redirect",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            402 => "Payment Required",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            406 => "Not Acceptable",
+            407 => "Proxy Authentication Required",
+            408 => "Request Timeout",
+            409 => "Conflict",
+            410 => "Gone",
+            411 => "Length Required",
+            412 => "Precondition Failed",
+            413 => "Payload Too Large",
+            414 => "URI Too Long",
+            415 => "Unsupported Media Type",
+            416 => "Range Not Satisfiable",
+            417 => "Expectation Failed",
+            421 => "Misdirected Request",
+            422 => "Unprocessable Content",
+            423 => "Locked",
+            424 => "Failed Dependency",
+            425 => "Too Early",
+            426 => "Upgrade Required",
+            428 => "Precondition Required",
+            429 => "Too Many Requests",
+            431 => "Request Header Fields Too Large",
+            451 => "Unavailable For Legal Reasons",
+            500 => "Internal Server Error",
+            501 => "Not Implemented",
+            502 => "Bad Gateway",
+            503 => "Service Unavailable",
+            504 => "Gateway Timeout",
+            505 => "HTTP Version Not Supported",
+            506 => "Variant Also Negotiates",
+            507 => "Insufficient Storage",
+            508 => "Loop Detected",
+            511 => "Network Authentication Required",
+            _ => return None,
+        };
+        Some(reason)
+    }
+}
+
+impl fmt::Display for StatusCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.canonical_reason() {
+            Some(reason) => write!(f, "{} {}", self.0, reason),
+            None => write!(f, "{}", self.0),
+        }
+    }
+}
+
+/// Parses a status line such as `HTTP/1.1 404 Not Found` into its code and
+/// reason phrase, which is everything after the space following the code.
+pub fn parse_status_line(line: &str) -> Result<(StatusCode, String), HttpResponseError> {
+    let invalid = || HttpResponseError::InvalidStatusLine(line.to_string());
+    let mut parts = line.splitn(3, ' ');
+    let version = parts.next().ok_or_else(invalid)?;
+    if !version.starts_with("HTTP/") {
+        return Err(invalid());
+    }
+    let code = parts.next().ok_or_else(invalid)?;
+    if code.len() != 3 || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let code = StatusCode::from_u16(code.parse().map_err(|_| invalid())?).map_err(|_| invalid())?;
+    let reason = parts.next().unwrap_or("").to_string();
+    Ok((code, reason))
+}
+
+#[test]
+fn status_classes() {
+    let not_found = StatusCode::from_u16(404).unwrap();
+    assert!(not_found.is_client_error());
+    assert!(!not_found.is_success());
+    assert_eq!("404 Not Found", not_found.to_string());
+    assert!(StatusCode::from_u16(204).unwrap().is_success());
+    assert!(StatusCode::from_u16(503).unwrap().is_server_error());
+    assert!(StatusCode::from_u16(301).unwrap().is_redirection());
+    assert!(StatusCode::from_u16(100).unwrap().is_informational());
+    assert!(StatusCode::from_u16(99).is_err());
+
+    let code = |code| StatusCode::from_u16(code).unwrap();
+    assert_eq!(Some(StatusClass::Informational), code(102).class());
+    assert_eq!(Some(StatusClass::Success), code(226).class());
+    assert_eq!(Some(StatusClass::ServerError), code(599).class());
+    assert_eq!(None, code(600).class());
+    assert!(code(100).is_interim() && code(102).is_interim() && code(103).is_interim());
+    assert!(!code(101).is_interim() && !code(200).is_interim());
+    for &no_content in &[100, 101, 204, 205, 304] {
+        assert!(code(no_content).forbids_content(), "{}", no_content);
+    }
+    assert!(!code(200).forbids_content() && !code(206).forbids_content());
+    assert!(code(226).is_instance_manipulated());
+    assert_eq!("226 IM Used", code(226).to_string());
+    assert_eq!("102 Processing", code(102).to_string());
+    assert!(StatusCode::from_u16(1000).is_err());
+}
+
+#[test]
+fn parse_status_lines() {
+    let (code, reason) = parse_status_line("HTTP/1.1 404 Not Found").unwrap();
+    assert_eq!(404, code.as_u16());
+    assert_eq!("Not Found", reason);
+
+    let (code, reason) = parse_status_line("HTTP/1.0 200").unwrap();
+    assert_eq!(200, code.as_u16());
+    assert_eq!("", reason);
+
+    let (_, reason) = parse_status_line("HTTP/1.1 200  Fine \t").unwrap();
+    assert_eq!(" Fine \t", reason);
+
+    assert!(parse_status_line("HTTP/1.1 20 OK").is_err());
+    assert!(parse_status_line("HTTP/1.1 abc OK").is_err());
+    assert!(parse_status_line("ICY 200 OK").is_err());
+}
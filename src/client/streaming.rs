@@ -0,0 +1,173 @@
+#![deny(missing_docs)]
+
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use bytes::Bytes;
+use tokio::prelude::*;
+use tokio::sync::mpsc;
+use url::Url;
+
+use super::simple_client::ResponseHead;
+#[cfg(any(feature = "https", feature = "native-tls"))]
+use super::tls::TlsInfo;
+use super::{HeaderMap, HttpResponseError, ProtocolWarning, StatusCode};
+
+/// A response whose body is read as it arrives, returned by
+/// `RequestBuilder::send_streaming`.
+#[derive(Debug)]
+pub struct StreamingResponse {
+    /// Status code from the status line.
+    pub status: StatusCode,
+    /// Reason phrase from the status line, exactly as sent by the server.
+    pub reason: String,
+    /// Response headers.
+    pub headers: HeaderMap,
+    url: Url,
+    warnings: Vec<ProtocolWarning>,
+    #[cfg(any(feature = "https", feature = "native-tls"))]
+    tls: Option<TlsInfo>,
+    body: BodyStream,
+}
+
+impl StreamingResponse {
+    pub(super) fn new(url: Url, head: ResponseHead, body: BodyStream) -> Self {
+        StreamingResponse {
+            status: head.status,
+            reason: head.reason,
+            headers: head.headers,
+            url,
+            warnings: head.warnings,
+            #[cfg(any(feature = "https", feature = "native-tls"))]
+            tls: head.tls,
+            body,
+        }
+    }
+
+    /// Returns the URL this response came from, after any redirects.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Returns the protocol deviations tolerated while parsing the head.
+    pub fn warnings(&self) -> &[ProtocolWarning] {
+        &self.warnings
+    }
+
+    /// Returns what was negotiated with the server if the response came over
+    /// TLS, after any redirects.
+    #[cfg(any(feature = "https", feature = "native-tls"))]
+    pub fn tls_info(&self) -> Option<&TlsInfo> {
+        self.tls.as_ref()
+    }
+
+    /// Turns a 4xx or 5xx response into an `HttpResponseError::Status` error.
+    pub fn error_for_status(self) -> Result<StreamingResponse, HttpResponseError> {
+        if self.status.is_client_error() || self.status.is_server_error() {
+            Err(HttpResponseError::Status(self.status))
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// Returns the body as a stream of pieces, exactly as received apart from
+    /// any chunked framing.
+    pub fn bytes_stream(self) -> BodyStream {
+        self.body
+    }
+
+    /// Writes the body to the file at `path` as it arrives and returns the
+    /// number of bytes written.
+    ///
+    /// The body goes to a temporary file next to `path`, which is renamed
+    /// over `path` only once the whole body has been received and flushed to
+    /// disk. On failure the temporary file is removed and `path` is left as
+    /// it was. The status is not checked; see `error_for_status`.
+    pub fn save_to<P: AsRef<Path>>(self, path: P) -> Result<u64, HttpResponseError> {
+        let path = path.as_ref();
+        let temporary = temporary_path(path)?;
+        let written = write_body(self.body, &temporary).and_then(|written| {
+            fs::rename(&temporary, path)?;
+            Ok(written)
+        });
+        if written.is_err() {
+            let _ = fs::remove_file(&temporary);
+        }
+        written
+    }
+}
+
+// A name for the partial download, hidden and unique to this process and
+// call, in the same directory so the final rename cannot cross file systems.
+fn temporary_path(path: &Path) -> Result<PathBuf, HttpResponseError> {
+    static DOWNLOADS: AtomicUsize = AtomicUsize::new(0);
+    let name = path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} does not name a file", path.display()),
+        )
+    })?;
+    Ok(path.with_file_name(format!(
+        ".{}.{}-{}.part",
+        name.to_string_lossy(),
+        process::id(),
+        DOWNLOADS.fetch_add(1, Ordering::Relaxed)
+    )))
+}
+
+fn write_body(body: BodyStream, path: &Path) -> Result<u64, HttpResponseError> {
+    let mut file = File::create(path)?;
+    let mut written = 0;
+    for chunk in body.wait() {
+        let chunk = chunk?;
+        file.write_all(&chunk)?;
+        written += chunk.len() as u64;
+    }
+    file.sync_all()?;
+    Ok(written)
+}
+
+/// The body of a `StreamingResponse`, yielded in pieces as they arrive.
+///
+/// Only a few pieces, each at most `ClientBuilder::read_buffer_size` long, are
+/// read ahead of the consumer, so memory use does not grow with the body.
+/// Outside an executor, `Stream::wait` turns it into a blocking iterator.
+///
+/// The connection is read on a thread of its own, which notices that the
+/// stream was dropped only once its pending read returns; it then closes the
+/// connection. Against a stalled server that is when the request's timeout
+/// or deadline fires, so set one if streams may be dropped early.
+pub struct BodyStream {
+    inner: mpsc::Receiver<Result<Bytes, HttpResponseError>>,
+}
+
+impl BodyStream {
+    pub(super) fn new(inner: mpsc::Receiver<Result<Bytes, HttpResponseError>>) -> Self {
+        BodyStream { inner }
+    }
+}
+
+impl fmt::Debug for BodyStream {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BodyStream").finish()
+    }
+}
+
+impl Stream for BodyStream {
+    type Item = Bytes;
+    type Error = HttpResponseError;
+
+    fn poll(&mut self) -> Poll<Option<Bytes>, HttpResponseError> {
+        match self.inner.poll() {
+            Ok(Async::Ready(Some(Ok(chunk)))) => Ok(Async::Ready(Some(chunk))),
+            Ok(Async::Ready(Some(Err(err)))) => Err(err),
+            Ok(Async::Ready(None)) => Ok(Async::Ready(None)),
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Err(err) => Err(HttpResponseError::from(io::Error::other(err))),
+        }
+    }
+}
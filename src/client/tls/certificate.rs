@@ -0,0 +1,194 @@
+#![deny(missing_docs)]
+
+use std::fmt;
+use std::str;
+
+use client::data_url::base64_decode;
+use client::HttpResponseError;
+
+/// An X.509 certificate to trust, see `ClientBuilder::add_root_certificate`.
+///
+/// Only the encoding is checked here; the certificate itself is parsed by the
+/// TLS backend when the client is built.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Certificate {
+    der: Vec<u8>,
+}
+
+impl Certificate {
+    /// Creates a certificate from its DER encoding.
+    pub fn from_der(der: &[u8]) -> Result<Certificate, HttpResponseError> {
+        // A certificate is an ASN.1 SEQUENCE.
+        if der.first() != Some(&0x30) {
+            return Err(HttpResponseError::InvalidCertificate(
+                "not a DER-encoded certificate".to_string(),
+            ));
+        }
+        Ok(Certificate { der: der.to_vec() })
+    }
+
+    /// Parses a PEM file holding exactly one certificate.
+    pub fn from_pem(pem: &[u8]) -> Result<Certificate, HttpResponseError> {
+        let mut certificates = Certificate::from_pem_bundle(pem)?;
+        if certificates.len() > 1 {
+            return Err(HttpResponseError::InvalidCertificate(format!(
+                "expected one certificate, found {}",
+                certificates.len()
+            )));
+        }
+        Ok(certificates.remove(0))
+    }
+
+    /// Parses every certificate in a PEM file, such as a CA bundle.
+    ///
+    /// Blocks other than `CERTIFICATE`, e.g. private keys, and text between
+    /// blocks are skipped. Fails if there is no certificate at all.
+    pub fn from_pem_bundle(pem: &[u8]) -> Result<Vec<Certificate>, HttpResponseError> {
+        let mut certificates = Vec::new();
+        for (label, der) in pem_blocks(pem)? {
+            if label == "CERTIFICATE" {
+                certificates.push(Certificate::from_der(&der)?);
+            }
+        }
+        if certificates.is_empty() {
+            return Err(invalid("no PEM certificate found"));
+        }
+        Ok(certificates)
+    }
+
+    /// Returns the DER encoding.
+    pub fn der(&self) -> &[u8] {
+        &self.der
+    }
+
+    // Wraps a certificate presented by a server, which the backend has
+    // already parsed.
+    pub(super) fn from_peer(der: Vec<u8>) -> Certificate {
+        Certificate { der }
+    }
+}
+
+impl fmt::Debug for Certificate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Certificate")
+            .field("der_len", &self.der.len())
+            .finish()
+    }
+}
+
+fn invalid(what: &str) -> HttpResponseError {
+    HttpResponseError::InvalidCertificate(what.to_string())
+}
+
+// Decodes the blocks of a PEM file into their labels and contents, skipping
+// any text between blocks.
+pub(super) fn pem_blocks(pem: &[u8]) -> Result<Vec<(&str, Vec<u8>)>, HttpResponseError> {
+    let pem = str::from_utf8(pem).map_err(|_| invalid("PEM is not valid UTF-8"))?;
+    let mut blocks = Vec::new();
+    let mut block: Option<(&str, String)> = None;
+    for line in pem.lines().map(str::trim) {
+        if let Some(label) = pem_boundary(line, "BEGIN") {
+            if block.is_some() {
+                return Err(invalid("PEM block is not terminated"));
+            }
+            block = Some((label, String::new()));
+        } else if let Some(label) = pem_boundary(line, "END") {
+            match block.take() {
+                Some((begin, base64)) if begin == label => {
+                    let der = base64_decode(base64.as_bytes())
+                        .ok_or_else(|| invalid("PEM block is not valid base64"))?;
+                    blocks.push((label, der));
+                }
+                _ => return Err(invalid("PEM END line does not match its BEGIN line")),
+            }
+        } else if let Some((_, ref mut base64)) = block {
+            base64.push_str(line);
+        }
+    }
+    if block.is_some() {
+        return Err(invalid("PEM block is not terminated"));
+    }
+    Ok(blocks)
+}
+
+// Encodes `der` as a PEM block, for backends that only take PEM.
+#[cfg(feature = "native-tls")]
+pub(super) fn pem_encode(label: &str, der: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut base64 = String::with_capacity(der.len() * 4 / 3 + 4);
+    for chunk in der.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (index, &byte)| {
+            bits | u32::from(byte) << (16 - 8 * index)
+        });
+        for index in 0..4 {
+            if index <= chunk.len() {
+                base64.push(ALPHABET[(bits >> (18 - 6 * index) & 0x3f) as usize] as char);
+            } else {
+                base64.push('=');
+            }
+        }
+    }
+    let mut pem = format!("-----BEGIN {}-----\n", label);
+    for line in base64.as_bytes().chunks(64) {
+        pem.push_str(str::from_utf8(line).unwrap());
+        pem.push('\n');
+    }
+    pem.push_str(&format!("-----END {}-----\n", label));
+    pem
+}
+
+// Returns the label of a `-----BEGIN label-----` or `-----END label-----` line.
+fn pem_boundary<'a>(line: &'a str, kind: &str) -> Option<&'a str> {
+    line.strip_prefix("-----")?
+        .strip_prefix(kind)?
+        .strip_prefix(' ')?
+        .strip_suffix("-----")
+}
+
+#[test]
+fn pem_bundle_parsing() {
+    let ca = include_bytes!("../../../tests/tls/ca.pem");
+    let leaf = include_bytes!("../../../tests/tls/localhost.pem");
+    let key = include_bytes!("../../../tests/tls/localhost-key.pem");
+    let der = include_bytes!("../../../tests/tls/ca.der");
+
+    let single = Certificate::from_pem(ca).unwrap();
+    assert_eq!(Certificate::from_der(der).unwrap(), single);
+
+    let mut bundle = b"# comment\n".to_vec();
+    bundle.extend_from_slice(leaf);
+    bundle.extend_from_slice(key);
+    bundle.extend_from_slice(ca);
+    let bundle = Certificate::from_pem_bundle(&bundle).unwrap();
+    assert_eq!(2, bundle.len());
+    assert_eq!(single, bundle[1]);
+    assert!(Certificate::from_pem(&[&leaf[..], &ca[..]].concat()).is_err());
+
+    for invalid in &[
+        &b""[..],
+        &key[..],
+        b"-----BEGIN CERTIFICATE-----\nMIIB\n",
+        b"-----BEGIN CERTIFICATE-----\n*\n-----END CERTIFICATE-----\n",
+        b"-----BEGIN CERTIFICATE-----\nAAAA\n-----END KEY-----\n",
+        b"-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----\n",
+    ] {
+        match Certificate::from_pem_bundle(invalid) {
+            Err(HttpResponseError::InvalidCertificate(_)) => {}
+            other => panic!("{:?} was accepted: {:?}", invalid, other),
+        }
+    }
+}
+
+#[cfg(feature = "native-tls")]
+#[test]
+fn pem_encode_round_trips() {
+    for len in 0..8 {
+        let der: Vec<u8> = (0..len * 23).map(|i| (i * 7) as u8).collect();
+        let pem = pem_encode("CERTIFICATE", &der);
+        assert!(pem.lines().all(|line| line.len() <= 64));
+        assert_eq!(
+            vec![("CERTIFICATE", der)],
+            pem_blocks(pem.as_bytes()).unwrap()
+        );
+    }
+}
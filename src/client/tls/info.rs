@@ -0,0 +1,42 @@
+#![deny(missing_docs)]
+
+use super::{Certificate, TlsVersion};
+
+/// What was negotiated with an `https` server, returned by
+/// `HttpResponse::tls_info`.
+///
+/// The `native-tls` backend only reports the ALPN protocol and the peer's
+/// leaf certificate; the platform libraries do not expose the rest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsInfo {
+    pub(super) version: Option<TlsVersion>,
+    pub(super) cipher_suite: Option<String>,
+    pub(super) alpn_protocol: Option<Vec<u8>>,
+    pub(super) peer_certificates: Vec<Certificate>,
+}
+
+impl TlsInfo {
+    /// Returns the protocol version, if the backend reports it.
+    pub fn version(&self) -> Option<TlsVersion> {
+        self.version
+    }
+
+    /// Returns the IANA name of the cipher suite, e.g.
+    /// `"TLS_AES_256_GCM_SHA384"`, if the backend reports it.
+    pub fn cipher_suite(&self) -> Option<&str> {
+        self.cipher_suite.as_deref()
+    }
+
+    /// Returns the protocol agreed through ALPN, if the server took part.
+    ///
+    /// The client only offers `http/1.1`, so that is the one protocol this
+    /// can be.
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.alpn_protocol.as_deref()
+    }
+
+    /// Returns the certificates the server presented, leaf first.
+    pub fn peer_certificates(&self) -> &[Certificate] {
+        &self.peer_certificates
+    }
+}
@@ -0,0 +1,111 @@
+#![deny(missing_docs)]
+
+// TLS for `https` URLs, through the backend picked by a feature: `https` for
+// rustls, or `native-tls` for the platform's own library and certificate
+// store. The rest of the client only sees `TlsConnector`.
+
+mod certificate;
+mod identity;
+mod info;
+#[cfg(feature = "native-tls")]
+mod native_connector;
+mod pin;
+#[cfg(feature = "https")]
+mod rustls_connector;
+mod version;
+
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+
+use tokio::net::TcpStream;
+use tokio::prelude::*;
+
+use self::pin::Pins;
+use super::transport::Transport;
+use super::HttpResponseError;
+
+pub use self::certificate::Certificate;
+pub use self::identity::Identity;
+pub use self::info::TlsInfo;
+pub use self::version::TlsVersion;
+
+// The protocol offered through ALPN; HTTP/2 is not implemented.
+pub(super) const ALPN_HTTP_1_1: &str = "http/1.1";
+
+// A pending TLS handshake.
+pub(super) type Handshake = Box<dyn Future<Item = Transport, Error = HttpResponseError>>;
+
+// Starts TLS sessions for a client; shared by every connection it opens.
+pub(super) trait TlsConnector: fmt::Debug + Send + Sync {
+    // Performs the TLS handshake over `socket`, verifying that the server's
+    // certificate chains to a trusted root and is valid for `host` unless
+    // the settings say otherwise.
+    fn connect(&self, host: &str, socket: TcpStream) -> Handshake;
+}
+
+// TLS options collected by a `ClientBuilder`.
+#[derive(Debug, Clone, Default)]
+pub(super) struct TlsSettings {
+    // Trusted in addition to the backend's default roots.
+    pub(super) roots: Vec<Certificate>,
+    // Presented to servers that ask for a client certificate.
+    pub(super) identity: Option<Identity>,
+    // Skips verifying the chain, and so the name too.
+    pub(super) accept_invalid_certs: bool,
+    // Verifies the chain but not that it was issued for the host.
+    pub(super) accept_invalid_hostnames: bool,
+    // Bounds on the protocol version; the backend's defaults when `None`.
+    pub(super) min_version: Option<TlsVersion>,
+    pub(super) max_version: Option<TlsVersion>,
+    // Checked after the usual verification has passed.
+    pins: Pins,
+}
+
+impl TlsSettings {
+    // Adds a pin for `host`, see `ClientBuilder::pin_spki_sha256`.
+    pub(super) fn add_pin(&mut self, host: &str, pin: &str) -> Result<(), HttpResponseError> {
+        self.pins.add(host, pin)
+    }
+
+    // Returns `true` if nothing was configured, so the shared default
+    // connector will do.
+    pub(super) fn is_default(&self) -> bool {
+        self.roots.is_empty()
+            && self.identity.is_none()
+            && !self.accept_invalid_certs
+            && !self.accept_invalid_hostnames
+            && self.min_version.is_none()
+            && self.max_version.is_none()
+            && self.pins.is_empty()
+    }
+}
+
+// Returns a connector of the enabled backend configured by `settings`.
+#[cfg(feature = "https")]
+pub(super) fn connector(
+    settings: &TlsSettings,
+) -> Result<Arc<dyn TlsConnector>, HttpResponseError> {
+    Ok(Arc::new(rustls_connector::RustlsConnector::new(settings)?))
+}
+
+// Returns a connector of the enabled backend configured by `settings`.
+#[cfg(feature = "native-tls")]
+pub(super) fn connector(
+    settings: &TlsSettings,
+) -> Result<Arc<dyn TlsConnector>, HttpResponseError> {
+    Ok(Arc::new(native_connector::NativeConnector::new(settings)?))
+}
+
+// Returns the connector with the default settings, shared by every client
+// that was not configured otherwise and created on first use.
+//
+// Only the platform library of `native-tls` can fail to initialize; the
+// error is returned and creation retried on the next call.
+pub(super) fn default_connector() -> Result<Arc<dyn TlsConnector>, HttpResponseError> {
+    static DEFAULT: OnceLock<Arc<dyn TlsConnector>> = OnceLock::new();
+    if let Some(connector) = DEFAULT.get() {
+        return Ok(connector.clone());
+    }
+    let created = connector(&TlsSettings::default())?;
+    Ok(DEFAULT.get_or_init(|| created).clone())
+}
@@ -0,0 +1,136 @@
+#![deny(missing_docs)]
+
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+use native_tls;
+use tokio::net::TcpStream;
+use tokio::prelude::*;
+use tokio_tls;
+
+use super::certificate::pem_encode;
+use super::identity::IdentityKind;
+use super::version;
+use super::{
+    Certificate, Handshake, ALPN_HTTP_1_1, Pins, TlsConnector, TlsInfo, TlsSettings, TlsVersion,
+};
+use client::transport::Transport;
+use client::HttpResponseError;
+
+// The backend of the `native-tls` feature: SChannel on Windows, Secure
+// Transport on macOS and OpenSSL elsewhere, trusting the certificates
+// installed in the operating system.
+pub(super) struct NativeConnector {
+    inner: tokio_tls::TlsConnector,
+    pins: Arc<Pins>,
+}
+
+impl NativeConnector {
+    // Trusts the operating system's roots, and those of `settings`; presents
+    // the identity of `settings`, if any.
+    pub(super) fn new(settings: &TlsSettings) -> Result<Self, HttpResponseError> {
+        let mut builder = native_tls::TlsConnector::builder();
+        for root in &settings.roots {
+            let root = native_tls::Certificate::from_der(root.der())
+                .map_err(|err| HttpResponseError::InvalidCertificate(err.to_string()))?;
+            builder.add_root_certificate(root);
+        }
+        if let Some(ref identity) = settings.identity {
+            let identity = match identity.kind {
+                IdentityKind::Pem { ref chain, ref key } => {
+                    let chain: String = chain
+                        .iter()
+                        .map(|certificate| pem_encode("CERTIFICATE", certificate.der()))
+                        .collect();
+                    let key = pem_encode("PRIVATE KEY", key);
+                    native_tls::Identity::from_pkcs8(chain.as_bytes(), key.as_bytes())
+                }
+                IdentityKind::Pkcs12 {
+                    ref der,
+                    ref password,
+                } => native_tls::Identity::from_pkcs12(der, password),
+            };
+            builder.identity(
+                identity.map_err(|err| HttpResponseError::InvalidCertificate(err.to_string()))?,
+            );
+        }
+        // Which versions are available is up to the platform library; this
+        // only rejects bounds that are out of order.
+        version::allowed(
+            settings.min_version,
+            settings.max_version,
+            &[
+                TlsVersion::Tls1_0,
+                TlsVersion::Tls1_1,
+                TlsVersion::Tls1_2,
+                TlsVersion::Tls1_3,
+            ],
+        )?;
+        if let Some(min) = settings.min_version {
+            builder.min_protocol_version(Some(protocol(min)));
+        }
+        if let Some(max) = settings.max_version {
+            builder.max_protocol_version(Some(protocol(max)));
+        }
+        builder
+            .request_alpns(&[ALPN_HTTP_1_1])
+            .danger_accept_invalid_certs(settings.accept_invalid_certs)
+            .danger_accept_invalid_hostnames(settings.accept_invalid_hostnames);
+        let connector = builder.build().map_err(tls_error)?;
+        Ok(NativeConnector {
+            inner: tokio_tls::TlsConnector::from(connector),
+            pins: Arc::new(settings.pins.clone()),
+        })
+    }
+}
+
+impl fmt::Debug for NativeConnector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NativeConnector").finish()
+    }
+}
+
+impl TlsConnector for NativeConnector {
+    // The platform libraries report I/O failures during the handshake as
+    // TLS errors too, so every failure here becomes `Tls`.
+    fn connect(&self, host: &str, socket: TcpStream) -> Handshake {
+        let pins = self.pins.clone();
+        let host = host.to_string();
+        Box::new(
+            self.inner
+                .connect(&host, socket)
+                .map_err(tls_error)
+                .and_then(move |stream| {
+                    let leaf = stream
+                        .get_ref()
+                        .peer_certificate()
+                        .map_err(tls_error)?
+                        .map(|leaf| leaf.to_der())
+                        .transpose()
+                        .map_err(tls_error)?;
+                    pins.check(&host, leaf.as_deref())?;
+                    let info = TlsInfo {
+                        version: None,
+                        cipher_suite: None,
+                        alpn_protocol: stream.get_ref().negotiated_alpn().map_err(tls_error)?,
+                        peer_certificates: leaf.into_iter().map(Certificate::from_peer).collect(),
+                    };
+                    Ok(Transport::Tls(Box::new(stream), info))
+                }),
+        )
+    }
+}
+
+fn tls_error(err: native_tls::Error) -> HttpResponseError {
+    HttpResponseError::Tls(io::Error::other(err))
+}
+
+fn protocol(version: TlsVersion) -> native_tls::Protocol {
+    match version {
+        TlsVersion::Tls1_0 => native_tls::Protocol::Tlsv10,
+        TlsVersion::Tls1_1 => native_tls::Protocol::Tlsv11,
+        TlsVersion::Tls1_2 => native_tls::Protocol::Tlsv12,
+        TlsVersion::Tls1_3 => native_tls::Protocol::Tlsv13,
+    }
+}
@@ -0,0 +1,139 @@
+#![deny(missing_docs)]
+
+use std::collections::HashMap;
+
+use sha2::{Digest, Sha256};
+
+use client::data_url::base64_decode;
+use client::HttpResponseError;
+
+// Expected SHA-256 hashes of the `SubjectPublicKeyInfo` of hosts' leaf
+// certificates, as used by HPKP and mobile pinning libraries.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Pins {
+    by_host: HashMap<String, Vec<[u8; 32]>>,
+}
+
+impl Pins {
+    // Returns `true` if no host has pins.
+    pub(super) fn is_empty(&self) -> bool {
+        self.by_host.is_empty()
+    }
+
+    // Adds `pin`, the base64 hash with an optional `sha256/` prefix, to the
+    // pins of `host`.
+    pub(super) fn add(&mut self, host: &str, pin: &str) -> Result<(), HttpResponseError> {
+        let base64 = pin.strip_prefix("sha256/").unwrap_or(pin);
+        let hash = base64_decode(base64.as_bytes())
+            .filter(|hash| hash.len() == 32)
+            .ok_or_else(|| {
+                HttpResponseError::InvalidCertificate(format!("invalid SPKI pin: {:?}", pin))
+            })?;
+        let mut pin = [0; 32];
+        pin.copy_from_slice(&hash);
+        self.by_host
+            .entry(host.to_ascii_lowercase())
+            .or_default()
+            .push(pin);
+        Ok(())
+    }
+
+    // Checks the DER `leaf` certificate `host` presented against its pins,
+    // if it has any; one matching pin is enough.
+    pub(super) fn check(&self, host: &str, leaf: Option<&[u8]>) -> Result<(), HttpResponseError> {
+        let pins = match self.by_host.get(&host.to_ascii_lowercase()) {
+            Some(pins) => pins,
+            None => return Ok(()),
+        };
+        let matches = leaf.and_then(spki).is_some_and(|spki| {
+            let hash = Sha256::digest(spki);
+            pins.iter().any(|pin| pin[..] == hash[..])
+        });
+        if matches {
+            Ok(())
+        } else {
+            Err(HttpResponseError::CertificatePinMismatch(host.to_string()))
+        }
+    }
+}
+
+// Returns the encoded `subjectPublicKeyInfo` of a DER certificate: the
+// seventh field of `tbsCertificate`, or the sixth without the optional
+// version.
+fn spki(certificate: &[u8]) -> Option<&[u8]> {
+    let (_, certificate, _) = der_element(certificate, 0x30)?;
+    let (_, mut fields, _) = der_element(certificate, 0x30)?;
+    if fields.first() == Some(&0xa0) {
+        fields = der_element(fields, 0xa0)?.2;
+    }
+    for _ in 0..5 {
+        fields = der_element(fields, *fields.first()?)?.2;
+    }
+    der_element(fields, 0x30).map(|(element, _, _)| element)
+}
+
+// Splits the DER element with `tag` off the front of `input`, returning the
+// whole element, its contents and what follows it.
+fn der_element(input: &[u8], tag: u8) -> Option<(&[u8], &[u8], &[u8])> {
+    if *input.first()? != tag {
+        return None;
+    }
+    let (length, header) = match *input.get(1)? {
+        short @ 0..=0x7f => (usize::from(short), 2),
+        long @ 0x81..=0x84 => {
+            let bytes = usize::from(long - 0x80);
+            let length = input
+                .get(2..2 + bytes)?
+                .iter()
+                .fold(0, |length, &b| length << 8 | usize::from(b));
+            (length, 2 + bytes)
+        }
+        _ => return None,
+    };
+    let end = header.checked_add(length)?;
+    if input.len() < end {
+        return None;
+    }
+    Some((&input[..end], &input[header..end], &input[end..]))
+}
+
+#[test]
+fn pins_match_the_leaf_spki() {
+    use super::Certificate;
+
+    let leaf = Certificate::from_pem(include_bytes!("../../../tests/tls/localhost.pem")).unwrap();
+    let ca = Certificate::from_pem(include_bytes!("../../../tests/tls/ca.pem")).unwrap();
+    let mut pins = Pins::default();
+    // Hashes computed with `openssl x509 -pubkey | openssl pkey -pubin
+    // -outform der | openssl dgst -sha256 -binary | base64`.
+    pins.add(
+        "Localhost",
+        "sha256/9DcGSW85AdAjc6FTOF8fPUdhOid3a9kyrrmIHwYv/uo=",
+    )
+    .unwrap();
+    pins.add("ca.test", "/AuKGt6VADI0vbbm/M96nJnK5w9uy3VnuU49G8g+tIk=")
+        .unwrap();
+
+    assert!(pins.check("localhost", Some(leaf.der())).is_ok());
+    assert!(pins.check("ca.test", Some(ca.der())).is_ok());
+    assert!(pins.check("unpinned.test", Some(ca.der())).is_ok());
+    for &(host, certificate) in &[
+        ("localhost", Some(ca.der())),
+        ("localhost", Some(&b"\x30\x00"[..])),
+        ("localhost", None),
+    ] {
+        match pins.check(host, certificate) {
+            Err(HttpResponseError::CertificatePinMismatch(ref pinned)) if pinned == host => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    for invalid in &[
+        "",
+        "sha256/",
+        "sha1/9DcGSW85AdAjc6FTOF8fPUdhOid3a9kyrrmIHwYv/uo=",
+        "AAAA",
+    ] {
+        assert!(pins.add("localhost", invalid).is_err(), "{:?}", invalid);
+    }
+}
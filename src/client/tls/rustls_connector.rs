@@ -0,0 +1,236 @@
+#![deny(missing_docs)]
+
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use tokio::net::TcpStream;
+use tokio::prelude::*;
+use tokio_rustls::rustls::{
+    self, CipherSuite, ClientConfig, ProtocolVersion, RootCertStore, ServerCertVerified,
+    ServerCertVerifier, Session, TLSError,
+};
+use tokio_rustls::webpki::{self, DNSNameRef};
+use webpki_roots;
+
+use super::identity::IdentityKind;
+use super::version;
+use super::{
+    Certificate, Handshake, ALPN_HTTP_1_1, Pins, TlsConnector, TlsInfo, TlsSettings, TlsVersion,
+};
+use client::transport::Transport;
+use client::HttpResponseError;
+
+// The rustls backend, enabled by the `https` feature.
+pub(super) struct RustlsConnector {
+    config: Arc<ClientConfig>,
+    pins: Arc<Pins>,
+    verifies: bool,
+}
+
+// Sent as the server name to IP addresses when nothing is verified: rustls
+// needs a DNS name, and `.invalid` can never be a real one.
+const PLACEHOLDER_NAME: &str = "ip-address.invalid";
+
+impl RustlsConnector {
+    // Trusts the Mozilla root certificates bundled by `webpki-roots`, and
+    // those of `settings`; presents the identity of `settings`, if any.
+    pub(super) fn new(settings: &TlsSettings) -> Result<Self, HttpResponseError> {
+        let mut config = ClientConfig::new();
+        config
+            .root_store
+            .add_server_trust_anchors(&webpki_roots::TLS_SERVER_ROOTS);
+        for root in &settings.roots {
+            config
+                .root_store
+                .add(&rustls::Certificate(root.der().to_vec()))
+                .map_err(|err| HttpResponseError::InvalidCertificate(format!("{:?}", err)))?;
+        }
+        if let Some(ref identity) = settings.identity {
+            let IdentityKind::Pem { ref chain, ref key } = identity.kind;
+            let key = rustls::PrivateKey(key.clone());
+            // `set_single_client_cert` panics on keys it cannot sign with.
+            rustls::sign::any_supported_type(&key).map_err(|()| {
+                HttpResponseError::InvalidCertificate("unsupported private key".to_string())
+            })?;
+            let chain = chain
+                .iter()
+                .map(|certificate| rustls::Certificate(certificate.der().to_vec()))
+                .collect();
+            config.set_single_client_cert(chain, key);
+        }
+        config.set_protocols(&[ALPN_HTTP_1_1.as_bytes().to_vec()]);
+        config.versions = version::allowed(
+            settings.min_version,
+            settings.max_version,
+            &[TlsVersion::Tls1_2, TlsVersion::Tls1_3],
+        )?
+        .into_iter()
+        .map(|version| match version {
+            TlsVersion::Tls1_3 => ProtocolVersion::TLSv1_3,
+            _ => ProtocolVersion::TLSv1_2,
+        })
+        .collect();
+        if settings.accept_invalid_certs {
+            config
+                .dangerous()
+                .set_certificate_verifier(Arc::new(AcceptAnyCertificate));
+        } else if settings.accept_invalid_hostnames {
+            config
+                .dangerous()
+                .set_certificate_verifier(Arc::new(IgnoreHostname));
+        }
+        Ok(RustlsConnector {
+            config: Arc::new(config),
+            pins: Arc::new(settings.pins.clone()),
+            verifies: !settings.accept_invalid_certs,
+        })
+    }
+}
+
+impl fmt::Debug for RustlsConnector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RustlsConnector").finish()
+    }
+}
+
+impl TlsConnector for RustlsConnector {
+    // webpki only verifies certificates for DNS names, so IP addresses are
+    // rejected before connecting unless nothing is verified.
+    fn connect(&self, host: &str, socket: TcpStream) -> Handshake {
+        let name = match DNSNameRef::try_from_ascii_str(host) {
+            Ok(name) => name,
+            Err(_) if !self.verifies => {
+                DNSNameRef::try_from_ascii_str(PLACEHOLDER_NAME).expect("valid DNS name")
+            }
+            Err(_) => {
+                return Box::new(future::err(HttpResponseError::Tls(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "{} is not a DNS name a certificate can be verified for",
+                        host
+                    ),
+                ))))
+            }
+        };
+        let pins = self.pins.clone();
+        let host = host.to_string();
+        Box::new(
+            tokio_rustls::TlsConnector::from(self.config.clone())
+                .connect(name, socket)
+                .map_err(|err| {
+                    if err
+                        .get_ref()
+                        .is_some_and(|inner| inner.is::<rustls::TLSError>())
+                    {
+                        HttpResponseError::Tls(err)
+                    } else {
+                        HttpResponseError::Io(err)
+                    }
+                })
+                .and_then(move |stream| {
+                    let session = stream.get_ref().1;
+                    let chain = session.get_peer_certificates().unwrap_or_default();
+                    pins.check(&host, chain.first().map(|leaf| &leaf.0[..]))?;
+                    let info = TlsInfo {
+                        version: session.get_protocol_version().and_then(tls_version),
+                        cipher_suite: session
+                            .get_negotiated_ciphersuite()
+                            .map(|suite| cipher_suite_name(suite.suite)),
+                        alpn_protocol: session.get_alpn_protocol().map(<[u8]>::to_vec),
+                        peer_certificates: chain
+                            .into_iter()
+                            .map(|certificate| Certificate::from_peer(certificate.0))
+                            .collect(),
+                    };
+                    Ok(Transport::Tls(Box::new(stream), info))
+                }),
+        )
+    }
+}
+
+// rustls names suites as registered with IANA, except for a `TLS13_` prefix
+// on those of TLS 1.3.
+fn cipher_suite_name(suite: CipherSuite) -> String {
+    format!("{:?}", suite).replacen("TLS13_", "TLS_", 1)
+}
+
+fn tls_version(version: ProtocolVersion) -> Option<TlsVersion> {
+    match version {
+        ProtocolVersion::TLSv1_0 => Some(TlsVersion::Tls1_0),
+        ProtocolVersion::TLSv1_1 => Some(TlsVersion::Tls1_1),
+        ProtocolVersion::TLSv1_2 => Some(TlsVersion::Tls1_2),
+        ProtocolVersion::TLSv1_3 => Some(TlsVersion::Tls1_3),
+        _ => None,
+    }
+}
+
+// Signature algorithms accepted in certificate chains, as by rustls itself.
+static SIGNATURE_ALGORITHMS: &[&webpki::SignatureAlgorithm] = &[
+    &webpki::ECDSA_P256_SHA256,
+    &webpki::ECDSA_P256_SHA384,
+    &webpki::ECDSA_P384_SHA256,
+    &webpki::ECDSA_P384_SHA384,
+    &webpki::RSA_PSS_2048_8192_SHA256_LEGACY_KEY,
+    &webpki::RSA_PSS_2048_8192_SHA384_LEGACY_KEY,
+    &webpki::RSA_PSS_2048_8192_SHA512_LEGACY_KEY,
+    &webpki::RSA_PKCS1_2048_8192_SHA256,
+    &webpki::RSA_PKCS1_2048_8192_SHA384,
+    &webpki::RSA_PKCS1_2048_8192_SHA512,
+    &webpki::RSA_PKCS1_3072_8192_SHA384,
+];
+
+// For `ClientBuilder::danger_accept_invalid_certs`.
+struct AcceptAnyCertificate;
+
+impl ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _roots: &RootCertStore,
+        _presented_certs: &[rustls::Certificate],
+        _dns_name: DNSNameRef,
+        _ocsp_response: &[u8],
+    ) -> Result<ServerCertVerified, TLSError> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+// For `ClientBuilder::danger_accept_invalid_hostnames`: the default
+// verification of rustls without its final name check.
+struct IgnoreHostname;
+
+impl ServerCertVerifier for IgnoreHostname {
+    fn verify_server_cert(
+        &self,
+        roots: &RootCertStore,
+        presented_certs: &[rustls::Certificate],
+        _dns_name: DNSNameRef,
+        _ocsp_response: &[u8],
+    ) -> Result<ServerCertVerified, TLSError> {
+        let (leaf, intermediates) = match presented_certs.split_first() {
+            Some((leaf, intermediates)) => (leaf, intermediates),
+            None => return Err(TLSError::NoCertificatesPresented),
+        };
+        let leaf = webpki::EndEntityCert::from(&leaf.0).map_err(TLSError::WebPKIError)?;
+        let intermediates: Vec<&[u8]> = intermediates
+            .iter()
+            .map(|certificate| &certificate.0[..])
+            .collect();
+        let anchors: Vec<webpki::TrustAnchor> = roots
+            .roots
+            .iter()
+            .map(|root| root.to_trust_anchor())
+            .collect();
+        let now = webpki::Time::try_from(SystemTime::now())
+            .map_err(|_| TLSError::FailedToGetCurrentTime)?;
+        leaf.verify_is_valid_tls_server_cert(
+            SIGNATURE_ALGORITHMS,
+            &webpki::TLSServerTrustAnchors(&anchors),
+            &intermediates,
+            now,
+        )
+        .map_err(TLSError::WebPKIError)?;
+        Ok(ServerCertVerified::assertion())
+    }
+}
@@ -0,0 +1,89 @@
+#![deny(missing_docs)]
+
+use std::fmt;
+
+use client::HttpResponseError;
+
+/// A TLS protocol version, for `ClientBuilder::min_tls_version` and
+/// `ClientBuilder::max_tls_version`.
+///
+/// Versions are ordered from oldest to newest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TlsVersion {
+    /// TLS 1.0, deprecated by RFC 8996.
+    Tls1_0,
+    /// TLS 1.1, deprecated by RFC 8996.
+    Tls1_1,
+    /// TLS 1.2.
+    Tls1_2,
+    /// TLS 1.3.
+    Tls1_3,
+}
+
+impl fmt::Display for TlsVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            TlsVersion::Tls1_0 => "TLS 1.0",
+            TlsVersion::Tls1_1 => "TLS 1.1",
+            TlsVersion::Tls1_2 => "TLS 1.2",
+            TlsVersion::Tls1_3 => "TLS 1.3",
+        })
+    }
+}
+
+// Returns the versions from `min` to `max` that `supported` lists, oldest
+// first; either bound may be open.
+pub(super) fn allowed(
+    min: Option<TlsVersion>,
+    max: Option<TlsVersion>,
+    supported: &[TlsVersion],
+) -> Result<Vec<TlsVersion>, HttpResponseError> {
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            return Err(HttpResponseError::UnsupportedTlsVersion(format!(
+                "minimum {} is newer than maximum {}",
+                min, max
+            )));
+        }
+    }
+    let allowed: Vec<_> = supported
+        .iter()
+        .cloned()
+        .filter(|&version| min.is_none_or(|min| min <= version))
+        .filter(|&version| max.is_none_or(|max| version <= max))
+        .collect();
+    if allowed.is_empty() {
+        return Err(HttpResponseError::UnsupportedTlsVersion(format!(
+            "the TLS backend supports none of {} to {}",
+            min.unwrap_or(TlsVersion::Tls1_0),
+            max.unwrap_or(TlsVersion::Tls1_3)
+        )));
+    }
+    Ok(allowed)
+}
+
+#[test]
+fn allowed_versions() {
+    let rustls = [TlsVersion::Tls1_2, TlsVersion::Tls1_3];
+    assert_eq!(
+        vec![TlsVersion::Tls1_2, TlsVersion::Tls1_3],
+        allowed(None, None, &rustls).unwrap()
+    );
+    assert_eq!(
+        vec![TlsVersion::Tls1_2],
+        allowed(Some(TlsVersion::Tls1_0), Some(TlsVersion::Tls1_2), &rustls).unwrap()
+    );
+    assert_eq!(
+        vec![TlsVersion::Tls1_3],
+        allowed(Some(TlsVersion::Tls1_3), None, &rustls).unwrap()
+    );
+    for &(min, max) in &[
+        (Some(TlsVersion::Tls1_3), Some(TlsVersion::Tls1_2)),
+        (None, Some(TlsVersion::Tls1_1)),
+    ] {
+        match allowed(min, max, &rustls) {
+            Err(HttpResponseError::UnsupportedTlsVersion(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+#![deny(missing_docs)]
+
+use std::io;
+
+use tokio::net::TcpStream;
+use tokio::prelude::*;
+
+#[cfg(any(feature = "https", feature = "native-tls"))]
+use super::tls::TlsInfo;
+
+// The connection under an `HttpStream`: plain TCP, or TLS over TCP for
+// `https` URLs.
+pub(super) enum Transport {
+    Plain(TcpStream),
+    #[cfg(any(feature = "https", feature = "native-tls"))]
+    Tls(Box<dyn TlsStream>, TlsInfo),
+}
+
+impl Transport {
+    // Returns what the TLS handshake negotiated, for a TLS connection.
+    #[cfg(any(feature = "https", feature = "native-tls"))]
+    pub(super) fn tls_info(&self) -> Option<&TlsInfo> {
+        match *self {
+            Transport::Plain(_) => None,
+            Transport::Tls(_, ref info) => Some(info),
+        }
+    }
+}
+
+// A TLS session from whichever backend is enabled.
+#[cfg(any(feature = "https", feature = "native-tls"))]
+pub(super) trait TlsStream: AsyncRead + AsyncWrite {}
+
+#[cfg(any(feature = "https", feature = "native-tls"))]
+impl<S: AsyncRead + AsyncWrite> TlsStream for S {}
+
+impl io::Read for Transport {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        match *self {
+            Transport::Plain(ref mut stream) => stream.read(buffer),
+            #[cfg(any(feature = "https", feature = "native-tls"))]
+            Transport::Tls(ref mut stream, _) => stream.read(buffer),
+        }
+    }
+}
+
+impl AsyncRead for Transport {}
+
+impl io::Write for Transport {
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        match *self {
+            Transport::Plain(ref mut stream) => stream.write(buffer),
+            #[cfg(any(feature = "https", feature = "native-tls"))]
+            Transport::Tls(ref mut stream, _) => stream.write(buffer),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match *self {
+            Transport::Plain(ref mut stream) => stream.flush(),
+            #[cfg(any(feature = "https", feature = "native-tls"))]
+            Transport::Tls(ref mut stream, _) => stream.flush(),
+        }
+    }
+}
+
+impl AsyncWrite for Transport {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        match *self {
+            Transport::Plain(ref mut stream) => AsyncWrite::shutdown(stream),
+            #[cfg(any(feature = "https", feature = "native-tls"))]
+            Transport::Tls(ref mut stream, _) => stream.shutdown(),
+        }
+    }
+}
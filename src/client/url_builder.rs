@@ -0,0 +1,346 @@
+#![deny(missing_docs)]
+
+use std::error;
+use std::fmt;
+
+#[cfg(feature = "json")]
+use serde::Serialize;
+#[cfg(feature = "json")]
+use serde_json::{self, Value};
+use url::{self, Host, Url};
+
+use super::HttpResponseError;
+
+/// Composes an `http` or `https` URL from its parts, checking each of them
+/// before any request is made.
+///
+/// Path segments, query parameters and the fragment are percent-encoded as
+/// needed. The first error is kept and returned by `build`.
+#[derive(Debug)]
+pub struct UrlBuilder {
+    url: Result<Url, UrlError>,
+}
+
+impl UrlBuilder {
+    /// Starts a URL at the root of `host`, a domain name or IP address;
+    /// IPv6 addresses are written in brackets.
+    pub fn new(scheme: &str, host: &str) -> Self {
+        // `Host::parse` alone would accept an empty host, and `Url::parse`
+        // alone one with a port or path in it.
+        let url = check_scheme(scheme).and_then(|scheme| {
+            Host::parse(host)
+                .ok()
+                .and_then(|_| Url::parse(&format!("{}://{}/", scheme, host)).ok())
+                .ok_or_else(|| UrlError::InvalidHost(host.to_string()))
+        });
+        UrlBuilder { url }
+    }
+
+    /// Starts from an existing URL, e.g. an API's base address, keeping its
+    /// path, query and fragment.
+    pub fn parse(base: &str) -> Self {
+        let url = Url::parse(base).map_err(UrlError::Parse).and_then(|url| {
+            check_scheme(url.scheme())?;
+            Ok(url)
+        });
+        UrlBuilder { url }
+    }
+
+    /// Sets the port. The scheme's default port is left out of the URL.
+    pub fn port(mut self, port: u16) -> Self {
+        if let Ok(ref mut url) = self.url {
+            // Only fails for URLs without a host, which are rejected earlier.
+            let _ = url.set_port(Some(port));
+        }
+        self
+    }
+
+    /// Appends one path segment. `/`, `?`, `#`, `%` and other reserved
+    /// characters in it are percent-encoded rather than starting a new
+    /// component.
+    pub fn path_segment(self, segment: &str) -> Self {
+        self.path_segments(&[segment])
+    }
+
+    /// Appends path segments in order. An empty trailing segment of the
+    /// current path, as in `https://example.com/v1/`, is replaced.
+    pub fn path_segments<S: AsRef<str>>(mut self, segments: &[S]) -> Self {
+        if let Ok(ref mut url) = self.url {
+            // http and https URLs always have a path, so this cannot fail.
+            if let Ok(mut path) = url.path_segments_mut() {
+                path.pop_if_empty();
+                for segment in segments {
+                    path.push(segment.as_ref());
+                }
+            }
+        }
+        self
+    }
+
+    /// Appends a query parameter, formatting `value` with `Display`, so
+    /// numbers and booleans can be passed as they are.
+    ///
+    /// Encoded as for `RequestBuilder::query`.
+    pub fn query<V: fmt::Display>(mut self, name: &str, value: V) -> Self {
+        if let Ok(ref mut url) = self.url {
+            url.query_pairs_mut().append_pair(name, &value.to_string());
+        }
+        self
+    }
+
+    /// Appends the fields of `value`, a struct or map, as query parameters.
+    /// Needs the `json` feature.
+    ///
+    /// Strings, numbers and booleans become one parameter each, and arrays of
+    /// them one parameter per element; `None` fields are left out. Fields
+    /// are appended in name order. Nested structs and maps are rejected with
+    /// `UrlError::QueryValue`.
+    #[cfg(feature = "json")]
+    pub fn query_serialize<T: Serialize + ?Sized>(mut self, value: &T) -> Self {
+        if let Ok(ref mut url) = self.url {
+            match query_pairs(value) {
+                Ok(pairs) => {
+                    if !pairs.is_empty() {
+                        url.query_pairs_mut().extend_pairs(pairs);
+                    }
+                }
+                Err(err) => self.url = Err(err),
+            }
+        }
+        self
+    }
+
+    /// Sets the fragment, or removes it with `None`.
+    pub fn fragment(mut self, fragment: Option<&str>) -> Self {
+        if let Ok(ref mut url) = self.url {
+            url.set_fragment(fragment);
+        }
+        self
+    }
+
+    /// Returns the URL, or the first error found while building it.
+    pub fn build(self) -> Result<Url, UrlError> {
+        self.url
+    }
+}
+
+/// A URL a request can be sent to: a `&str` or `String` to parse, a `Url`,
+/// or a `UrlBuilder`.
+///
+/// A URL that does not parse fails the request with
+/// `HttpResponseError::ParseURL`, and a `UrlBuilder` error with
+/// `HttpResponseError::InvalidUrl`.
+pub trait IntoUrl {
+    /// Returns the URL.
+    fn into_url(self) -> Result<Url, HttpResponseError>;
+}
+
+impl IntoUrl for &str {
+    fn into_url(self) -> Result<Url, HttpResponseError> {
+        Url::parse(self).map_err(HttpResponseError::from)
+    }
+}
+
+impl IntoUrl for &String {
+    fn into_url(self) -> Result<Url, HttpResponseError> {
+        self.as_str().into_url()
+    }
+}
+
+impl IntoUrl for String {
+    fn into_url(self) -> Result<Url, HttpResponseError> {
+        self.as_str().into_url()
+    }
+}
+
+impl IntoUrl for Url {
+    fn into_url(self) -> Result<Url, HttpResponseError> {
+        Ok(self)
+    }
+}
+
+impl IntoUrl for &Url {
+    fn into_url(self) -> Result<Url, HttpResponseError> {
+        Ok(self.clone())
+    }
+}
+
+impl IntoUrl for UrlBuilder {
+    fn into_url(self) -> Result<Url, HttpResponseError> {
+        self.build().map_err(HttpResponseError::from)
+    }
+}
+
+/// Why a `UrlBuilder` could not build its URL.
+#[derive(Debug)]
+pub enum UrlError {
+    /// The base URL could not be parsed.
+    Parse(url::ParseError),
+    /// The scheme is not `http` or `https`; holds the scheme.
+    UnsupportedScheme(String),
+    /// The host is not a valid domain name or IP address; holds the host.
+    InvalidHost(String),
+    /// The value given to `query_serialize` is not a struct or map.
+    #[cfg(feature = "json")]
+    QueryNotAMap,
+    /// A field given to `query_serialize` cannot be written as a query
+    /// parameter; holds its name.
+    #[cfg(feature = "json")]
+    QueryValue(String),
+    /// The value given to `query_serialize` failed to serialize.
+    #[cfg(feature = "json")]
+    QuerySerialize(serde_json::Error),
+}
+
+impl fmt::Display for UrlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            UrlError::Parse(ref err) => write!(f, "invalid base URL: {}", err),
+            UrlError::UnsupportedScheme(ref scheme) => {
+                write!(f, "unsupported scheme: {:?}", scheme)
+            }
+            UrlError::InvalidHost(ref host) => write!(f, "invalid host: {:?}", host),
+            #[cfg(feature = "json")]
+            UrlError::QueryNotAMap => write!(f, "query is not a struct or map"),
+            #[cfg(feature = "json")]
+            UrlError::QueryValue(ref name) => {
+                write!(
+                    f,
+                    "query field {:?} is not a scalar or a list of them",
+                    name
+                )
+            }
+            #[cfg(feature = "json")]
+            UrlError::QuerySerialize(ref err) => write!(f, "query failed to serialize: {}", err),
+        }
+    }
+}
+
+impl error::Error for UrlError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            UrlError::Parse(ref err) => Some(err),
+            #[cfg(feature = "json")]
+            UrlError::QuerySerialize(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+fn check_scheme(scheme: &str) -> Result<String, UrlError> {
+    let lowercase = scheme.to_ascii_lowercase();
+    match lowercase.as_str() {
+        "http" | "https" => Ok(lowercase),
+        _ => Err(UrlError::UnsupportedScheme(scheme.to_string())),
+    }
+}
+
+#[cfg(feature = "json")]
+fn query_pairs<T: Serialize + ?Sized>(value: &T) -> Result<Vec<(String, String)>, UrlError> {
+    let fields = match serde_json::to_value(value).map_err(UrlError::QuerySerialize)? {
+        Value::Object(fields) => fields,
+        _ => return Err(UrlError::QueryNotAMap),
+    };
+    let mut pairs = Vec::new();
+    for (name, value) in fields {
+        let values = match value {
+            Value::Array(values) => values,
+            value => vec![value],
+        };
+        for value in values {
+            let value = match value {
+                Value::Null => continue,
+                Value::Bool(value) => value.to_string(),
+                Value::Number(value) => value.to_string(),
+                Value::String(value) => value,
+                Value::Array(_) | Value::Object(_) => return Err(UrlError::QueryValue(name)),
+            };
+            pairs.push((name.clone(), value));
+        }
+    }
+    Ok(pairs)
+}
+
+#[test]
+fn builds_urls_from_parts() {
+    let url = UrlBuilder::new("HTTP", "Example.COM")
+        .port(8080)
+        .path_segment("a/b?c")
+        .path_segments(&["d", "é"])
+        .query("flag", true)
+        .query("q", "x&y z")
+        .fragment(Some("top"))
+        .build()
+        .unwrap();
+    assert_eq!(
+        "http://example.com:8080/a%2Fb%3Fc/d/%C3%A9?flag=true&q=x%26y+z#top",
+        url.as_str()
+    );
+
+    let url = UrlBuilder::parse("https://api.example.com/v1/?key=1#old")
+        .path_segments(&["users", "42"])
+        .port(443)
+        .fragment(None)
+        .build()
+        .unwrap();
+    assert_eq!("https://api.example.com/v1/users/42?key=1", url.as_str());
+
+    let url = UrlBuilder::new("http", "[::1]").build().unwrap();
+    assert_eq!("http://[::1]/", url.as_str());
+}
+
+#[test]
+fn rejects_invalid_parts() {
+    match UrlBuilder::new("ftp", "example.com").build() {
+        Err(UrlError::UnsupportedScheme(ref scheme)) if scheme == "ftp" => {}
+        other => panic!("{:?}", other),
+    }
+    for host in &["", "exa mple.com", "example.com:80", "a/b", "::1"] {
+        match UrlBuilder::new("http", host).query("a", 1).build() {
+            Err(UrlError::InvalidHost(_)) => {}
+            other => panic!("{:?} was accepted: {:?}", host, other),
+        }
+    }
+    match UrlBuilder::parse("not a url").build() {
+        Err(UrlError::Parse(_)) => {}
+        other => panic!("{:?}", other),
+    }
+    match UrlBuilder::parse("data:,hi").build() {
+        Err(UrlError::UnsupportedScheme(_)) => {}
+        other => panic!("{:?}", other),
+    }
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn serializes_typed_queries() {
+    let search: Value = serde_json::from_str(
+        r#"{"q": "a b", "page": 2, "exact": false, "tags": ["x", "y"], "lang": null}"#,
+    )
+    .unwrap();
+    let url = UrlBuilder::new("https", "example.com")
+        .query("first", 0)
+        .query_serialize(&search)
+        .build()
+        .unwrap();
+    assert_eq!(
+        Some("first=0&exact=false&page=2&q=a+b&tags=x&tags=y"),
+        url.query()
+    );
+
+    match UrlBuilder::new("https", "example.com")
+        .query_serialize(&[1, 2])
+        .build()
+    {
+        Err(UrlError::QueryNotAMap) => {}
+        other => panic!("{:?}", other),
+    }
+    let nested: Value = serde_json::from_str(r#"{"filter": {"a": 1}}"#).unwrap();
+    match UrlBuilder::new("https", "example.com")
+        .query_serialize(&nested)
+        .build()
+    {
+        Err(UrlError::QueryValue(ref name)) if name == "filter" => {}
+        other => panic!("{:?}", other),
+    }
+}
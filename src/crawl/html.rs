@@ -0,0 +1,342 @@
+#![deny(missing_docs)]
+
+use std::cmp;
+
+use tokio::prelude::*;
+
+use client::charset;
+use client::{HttpResponse, HttpResponseError, StreamingResponse};
+
+use super::RobotsTag;
+
+/// Number of bytes of a document inspected by `HtmlHints::scan`.
+pub const DEFAULT_SCAN_LIMIT: usize = 64 * 1024;
+
+/// A `<meta http-equiv="refresh">` redirect.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetaRefresh {
+    /// Seconds to wait before refreshing.
+    pub delay: u64,
+    /// Target URL as written in the document, if any. Relative URLs are not resolved.
+    pub url: Option<String>,
+}
+
+/// Navigation hints found in the head of an HTML document.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HtmlHints {
+    /// The first `<meta http-equiv="refresh">` in the document.
+    pub refresh: Option<MetaRefresh>,
+    /// The `href` of the first `<link rel="canonical">` in the document.
+    pub canonical: Option<String>,
+    /// Directives from `<meta name="robots">` and crawler-specific meta tags.
+    pub robots: RobotsTag,
+}
+
+impl HtmlHints {
+    /// Scans the first `DEFAULT_SCAN_LIMIT` bytes of `document`.
+    pub fn scan(document: &str) -> Self {
+        HtmlHints::scan_with_limit(document, DEFAULT_SCAN_LIMIT)
+    }
+
+    /// Scans the first `limit` bytes of `document`.
+    ///
+    /// Scanning stops early at `</head>` or `<body>`, and content inside
+    /// comments is ignored.
+    pub fn scan_with_limit(document: &str, limit: usize) -> Self {
+        let mut end = cmp::min(limit, document.len());
+        while !document.is_char_boundary(end) {
+            end -= 1;
+        }
+        HtmlHints::scan_all(&document[..end])
+    }
+
+    /// Scans the body of `response`.
+    pub fn from_response(response: &HttpResponse) -> Self {
+        HtmlHints::scan(&response.body.text)
+    }
+
+    /// Scans the first `limit` bytes of the body of `response`, reading it
+    /// only that far.
+    ///
+    /// The bytes are decoded in the charset named by `Content-Type`, as for
+    /// `from_response`. Fails if the body cannot be read up to `limit`
+    /// bytes or its end.
+    pub fn from_streaming_response(
+        response: StreamingResponse,
+        limit: usize,
+    ) -> Result<Self, HttpResponseError> {
+        let content_type = response.headers.get("Content-Type").map(str::to_string);
+        let mut document = Vec::new();
+        if limit > 0 {
+            for chunk in response.bytes_stream().wait() {
+                let chunk = chunk?;
+                let wanted = cmp::min(limit - document.len(), chunk.len());
+                document.extend_from_slice(&chunk[..wanted]);
+                if document.len() == limit {
+                    break;
+                }
+            }
+        }
+        let document = charset::decode(&document, content_type.as_deref());
+        Ok(HtmlHints::scan_all(&document))
+    }
+
+    // Scans all of `document`, stopping early at `</head>` or `<body>`.
+    fn scan_all(document: &str) -> Self {
+        let mut hints = HtmlHints::default();
+        let mut rest = document;
+        while let Some(start) = rest.find('<') {
+            rest = &rest[start + 1..];
+            if rest.starts_with("!--") {
+                match rest.find("-->") {
+                    Some(close) => rest = &rest[close + 3..],
+                    None => break,
+                }
+                continue;
+            }
+            let close = match tag_end(rest) {
+                Some(close) => close,
+                None => break,
+            };
+            let tag = &rest[..close];
+            rest = &rest[close + 1..];
+
+            let name_end = tag
+                .find(|c: char| c.is_ascii_whitespace() || c == '/')
+                .unwrap_or(tag.len());
+            let name = &tag[..name_end];
+            let attributes = parse_attributes(&tag[name_end..]);
+            if name.eq_ignore_ascii_case("meta") {
+                let is_refresh = attribute(&attributes, "http-equiv")
+                    .is_some_and(|value| value.eq_ignore_ascii_case("refresh"));
+                if is_refresh && hints.refresh.is_none() {
+                    hints.refresh = attribute(&attributes, "content").and_then(parse_refresh);
+                }
+                if let (Some(name), Some(content)) = (
+                    attribute(&attributes, "name"),
+                    attribute(&attributes, "content"),
+                ) {
+                    if is_robots_meta(name) {
+                        hints.robots.add_meta(name, content);
+                    }
+                }
+            } else if name.eq_ignore_ascii_case("link") && hints.canonical.is_none() {
+                let is_canonical = attribute(&attributes, "rel").is_some_and(|value| {
+                    value
+                        .split_ascii_whitespace()
+                        .any(|rel| rel.eq_ignore_ascii_case("canonical"))
+                });
+                if is_canonical {
+                    hints.canonical = attribute(&attributes, "href")
+                        .map(|href| href.trim().to_string())
+                        .filter(|href| !href.is_empty());
+                }
+            } else if name.eq_ignore_ascii_case("/head") || name.eq_ignore_ascii_case("body") {
+                break;
+            }
+        }
+        hints
+    }
+}
+
+// Offset of the `>` that closes the tag at the start of `rest`, skipping any
+// inside quoted attribute values.
+fn tag_end(rest: &str) -> Option<usize> {
+    let bytes = rest.as_bytes();
+    let mut after_equals = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'>' => return Some(i),
+            b'=' => after_equals = true,
+            quote @ (b'"' | b'\'') if after_equals => {
+                i += 1 + rest[i + 1..].find(quote as char)?;
+                after_equals = false;
+            }
+            byte if byte.is_ascii_whitespace() => {}
+            _ => after_equals = false,
+        }
+        i += 1;
+    }
+    None
+}
+
+// Crawler names that are known to read their own meta tag.
+fn is_robots_meta(name: &str) -> bool {
+    ["robots", "googlebot", "googlebot-news", "bingbot"]
+        .iter()
+        .any(|known| known.eq_ignore_ascii_case(name))
+}
+
+fn attribute<'a>(attributes: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+fn parse_attributes(mut input: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    loop {
+        input = input.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == '/');
+        if input.is_empty() {
+            return attributes;
+        }
+        let name_end = input
+            .find(|c: char| c.is_ascii_whitespace() || c == '=' || c == '/')
+            .unwrap_or(input.len());
+        let name = input[..name_end].to_string();
+        input = input[name_end..].trim_start();
+        if !input.starts_with('=') {
+            attributes.push((name, String::new()));
+            continue;
+        }
+        input = input[1..].trim_start();
+        let value = match input.chars().next() {
+            Some(quote @ '"') | Some(quote @ '\'') => {
+                let value_end = input[1..].find(quote).map_or(input.len(), |end| end + 1);
+                let value = &input[1..value_end];
+                input = &input[cmp::min(value_end + 1, input.len())..];
+                value
+            }
+            _ => {
+                let value_end = input
+                    .find(|c: char| c.is_ascii_whitespace())
+                    .unwrap_or(input.len());
+                let value = &input[..value_end];
+                input = &input[value_end..];
+                value
+            }
+        };
+        attributes.push((name, value.to_string()));
+    }
+}
+
+// Parses `content` values such as `5`, `0; url=/next` or `3,URL='/next'`.
+fn parse_refresh(content: &str) -> Option<MetaRefresh> {
+    let content = content.trim();
+    let digits = content
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(content.len());
+    let delay = content[..digits].parse().ok()?;
+    // Fractional delays are allowed but only the integer part is kept.
+    let rest = content[digits..].trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+    let rest = rest.trim_start();
+    if rest.is_empty() {
+        return Some(MetaRefresh { delay, url: None });
+    }
+    let mut url = rest.strip_prefix(|c| c == ';' || c == ',')?.trim_start();
+    if url
+        .get(..3)
+        .is_some_and(|name| name.eq_ignore_ascii_case("url"))
+    {
+        if let Some(after) = url[3..].trim_start().strip_prefix('=') {
+            url = after.trim_start();
+        }
+    }
+    if url.starts_with('"') || url.starts_with('\'') {
+        let quote = &url[..1];
+        url = &url[1..];
+        if let Some(end) = url.find(quote) {
+            url = &url[..end];
+        }
+    }
+    let url = url.trim();
+    Some(MetaRefresh {
+        delay,
+        url: if url.is_empty() {
+            None
+        } else {
+            Some(url.to_string())
+        },
+    })
+}
+
+#[test]
+fn scan_refresh_and_canonical() {
+    let hints = HtmlHints::scan(
+        r#"<!DOCTYPE html><html><head>
+        <META HTTP-EQUIV="Refresh" CONTENT="5; URL='https://example.com/next'">
+        <link rel="alternate canonical" href=" /article/1 ">
+        </head><body></body></html>"#,
+    );
+    assert_eq!(
+        Some(MetaRefresh {
+            delay: 5,
+            url: Some("https://example.com/next".to_string()),
+        }),
+        hints.refresh
+    );
+    assert_eq!(Some("/article/1".to_string()), hints.canonical);
+    assert!(hints.robots.is_empty());
+}
+
+#[test]
+fn scan_robots_meta() {
+    let hints = HtmlHints::scan(
+        r#"<head><meta name="robots" content="noindex">
+        <meta name="googlebot" content="nosnippet">
+        <meta name="description" content="none"></head>"#,
+    );
+    assert!(hints.robots.directives().noindex);
+    assert!(!hints.robots.directives().nosnippet);
+    assert!(hints.robots.directives_for("googlebot").nosnippet);
+}
+
+#[test]
+fn scan_refresh_variants() {
+    assert_eq!(
+        Some(MetaRefresh {
+            delay: 0,
+            url: None
+        }),
+        parse_refresh("0")
+    );
+    assert_eq!(
+        Some(MetaRefresh {
+            delay: 3,
+            url: Some("/next".to_string()),
+        }),
+        parse_refresh("3.5,/next")
+    );
+    assert_eq!(None, parse_refresh("soon; url=/next"));
+    assert_eq!(
+        Some(MetaRefresh {
+            delay: 1,
+            url: Some("ü/x".to_string()),
+        }),
+        parse_refresh("1; ü/x")
+    );
+}
+
+#[test]
+fn scan_stops_at_body_and_skips_comments() {
+    let hints = HtmlHints::scan(
+        "<head><!-- <link rel=canonical href=/commented> --></head>\
+         <body><link rel=canonical href=/in-body></body>",
+    );
+    assert_eq!(HtmlHints::default(), hints);
+}
+
+#[test]
+fn scan_respects_limit() {
+    let document = format!(
+        "<head>{}<link rel=canonical href=/late></head>",
+        " ".repeat(100)
+    );
+    assert_eq!(None, HtmlHints::scan_with_limit(&document, 64).canonical);
+    assert_eq!(
+        Some("/late".to_string()),
+        HtmlHints::scan_with_limit(&document, 1024).canonical
+    );
+}
+
+#[test]
+fn scan_skips_quoted_angle_brackets() {
+    let hints = HtmlHints::scan(
+        r#"<head><meta name="robots" title="a > b" data-x='>' content="noindex">
+        <link title=it's rel=canonical href="/a>b"></head>"#,
+    );
+    assert!(hints.robots.directives().noindex);
+    assert_eq!(Some("/a>b".to_string()), hints.canonical);
+}
@@ -0,0 +1,7 @@
+#![deny(missing_docs)]
+//! Helpers for crawlers and scrapers, enabled by the `crawl` feature.
+mod html;
+mod robots_tag;
+
+pub use self::html::{HtmlHints, MetaRefresh, DEFAULT_SCAN_LIMIT};
+pub use self::robots_tag::{ImagePreview, RobotsDirectives, RobotsTag};
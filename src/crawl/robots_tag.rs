@@ -0,0 +1,251 @@
+#![deny(missing_docs)]
+
+use std::time::SystemTime;
+
+use client::HttpHeaders;
+use httpdate;
+
+/// Preview size allowed by `max-image-preview`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImagePreview {
+    /// `none`: no image preview.
+    None,
+    /// `standard`: a default-sized preview.
+    Standard,
+    /// `large`: a preview up to the width of the viewport.
+    Large,
+}
+
+/// Indexing directives from `X-Robots-Tag` or `<meta name="robots">`.
+///
+/// `none` sets both `noindex` and `nofollow`; `all` sets nothing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RobotsDirectives {
+    /// Do not show the page in search results.
+    pub noindex: bool,
+    /// Do not follow links on the page.
+    pub nofollow: bool,
+    /// Do not show a cached copy.
+    pub noarchive: bool,
+    /// Do not show a text snippet or video preview.
+    pub nosnippet: bool,
+    /// Do not offer a translation.
+    pub notranslate: bool,
+    /// Do not index images on the page.
+    pub noimageindex: bool,
+    /// Maximum snippet length in characters; `-1` means no limit.
+    pub max_snippet: Option<i64>,
+    /// Maximum image preview size.
+    pub max_image_preview: Option<ImagePreview>,
+    /// Maximum video preview length in seconds; `-1` means no limit.
+    pub max_video_preview: Option<i64>,
+    /// Time after which the page should no longer be shown.
+    pub unavailable_after: Option<SystemTime>,
+}
+
+impl RobotsDirectives {
+    fn apply(&mut self, directive: &str) {
+        let (name, value) = match directive.find(':') {
+            Some(colon) => (
+                directive[..colon].trim(),
+                Some(directive[colon + 1..].trim()),
+            ),
+            None => (directive.trim(), None),
+        };
+        let name = name.to_ascii_lowercase();
+        match (name.as_str(), value) {
+            ("noindex", None) => self.noindex = true,
+            ("nofollow", None) => self.nofollow = true,
+            ("none", None) => {
+                self.noindex = true;
+                self.nofollow = true;
+            }
+            ("noarchive", None) | ("nocache", None) => self.noarchive = true,
+            ("nosnippet", None) => self.nosnippet = true,
+            ("notranslate", None) => self.notranslate = true,
+            ("noimageindex", None) => self.noimageindex = true,
+            ("max-snippet", Some(value)) => self.max_snippet = value.parse().ok(),
+            ("max-video-preview", Some(value)) => self.max_video_preview = value.parse().ok(),
+            ("max-image-preview", Some(value)) => {
+                self.max_image_preview = match value.to_ascii_lowercase().as_str() {
+                    "none" => Some(ImagePreview::None),
+                    "standard" => Some(ImagePreview::Standard),
+                    "large" => Some(ImagePreview::Large),
+                    _ => None,
+                }
+            }
+            ("unavailable_after", Some(value)) => {
+                self.unavailable_after = httpdate::parse_http_date(value).ok()
+            }
+            _ => {}
+        }
+    }
+
+    fn merge(&mut self, other: &RobotsDirectives) {
+        self.noindex |= other.noindex;
+        self.nofollow |= other.nofollow;
+        self.noarchive |= other.noarchive;
+        self.nosnippet |= other.nosnippet;
+        self.notranslate |= other.notranslate;
+        self.noimageindex |= other.noimageindex;
+        self.max_snippet = other.max_snippet.or(self.max_snippet);
+        self.max_image_preview = other.max_image_preview.or(self.max_image_preview);
+        self.max_video_preview = other.max_video_preview.or(self.max_video_preview);
+        self.unavailable_after = other.unavailable_after.or(self.unavailable_after);
+    }
+}
+
+const VALUED_DIRECTIVES: [&str; 4] = [
+    "max-snippet",
+    "max-image-preview",
+    "max-video-preview",
+    "unavailable_after",
+];
+
+/// Robots directives grouped by the user agent they apply to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RobotsTag {
+    rules: Vec<(Option<String>, RobotsDirectives)>,
+}
+
+impl RobotsTag {
+    /// Collects every `X-Robots-Tag` header in `headers`.
+    pub fn from_headers(headers: &HttpHeaders) -> Self {
+        let mut tag = RobotsTag::default();
+        for header in headers
+            .iter()
+            .filter(|header| header.name.eq_ignore_ascii_case("X-Robots-Tag"))
+        {
+            tag.add_header_value(&header.content);
+        }
+        tag
+    }
+
+    /// Adds one `X-Robots-Tag` value, e.g. `googlebot: noindex, nofollow`.
+    pub fn add_header_value(&mut self, value: &str) {
+        let value = value.trim();
+        let (user_agent, directives) = match value.find(':') {
+            Some(colon) => {
+                let prefix = value[..colon].trim();
+                let is_directive = VALUED_DIRECTIVES
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(prefix));
+                if is_directive || prefix.contains(',') {
+                    (None, value)
+                } else {
+                    (Some(prefix), &value[colon + 1..])
+                }
+            }
+            None => (None, value),
+        };
+        self.add(user_agent, directives);
+    }
+
+    /// Adds the `content` of a `<meta name="...">` tag, where `name` is
+    /// `robots` for all crawlers or a specific crawler's name.
+    pub fn add_meta(&mut self, name: &str, content: &str) {
+        if name.eq_ignore_ascii_case("robots") {
+            self.add(None, content);
+        } else {
+            self.add(Some(name), content);
+        }
+    }
+
+    /// Returns `true` if no directives were found.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Directives that apply to every crawler.
+    pub fn directives(&self) -> RobotsDirectives {
+        self.collect(None)
+    }
+
+    /// Directives that apply to `user_agent`: the general ones combined with
+    /// those addressed to it by name.
+    pub fn directives_for(&self, user_agent: &str) -> RobotsDirectives {
+        self.collect(Some(user_agent))
+    }
+
+    fn add(&mut self, user_agent: Option<&str>, directives: &str) {
+        let mut parsed = RobotsDirectives::default();
+        // unavailable_after takes a date which may itself contain a comma, as
+        // in `unavailable_after: Sun, 06 Nov 1994 08:49:37 GMT`.
+        let mut date: Option<String> = None;
+        for directive in directives.split(',').filter(|d| !d.trim().is_empty()) {
+            if let Some(mut pending) = date.take() {
+                let continues = directive
+                    .trim_start()
+                    .starts_with(|c: char| c.is_ascii_digit());
+                if continues {
+                    pending.push(',');
+                    pending.push_str(directive);
+                    parsed.apply(&pending);
+                    continue;
+                }
+                parsed.apply(&pending);
+            }
+            let is_date = directive
+                .trim_start()
+                .get(..17)
+                .is_some_and(|name| name.eq_ignore_ascii_case("unavailable_after"));
+            if is_date {
+                date = Some(directive.to_string());
+            } else {
+                parsed.apply(directive);
+            }
+        }
+        if let Some(pending) = date {
+            parsed.apply(&pending);
+        }
+        let user_agent = user_agent.map(|agent| agent.to_ascii_lowercase());
+        self.rules.push((user_agent, parsed));
+    }
+
+    fn collect(&self, user_agent: Option<&str>) -> RobotsDirectives {
+        let mut directives = RobotsDirectives::default();
+        for (agent, rule) in &self.rules {
+            let applies = match (agent, user_agent) {
+                (None, _) => true,
+                (Some(agent), Some(user_agent)) => agent.eq_ignore_ascii_case(user_agent),
+                (Some(_), None) => false,
+            };
+            if applies {
+                directives.merge(rule);
+            }
+        }
+        directives
+    }
+}
+
+#[test]
+fn header_values() {
+    let mut tag = RobotsTag::default();
+    tag.add_header_value("noindex, max-snippet: 20");
+    tag.add_header_value("googlebot: nofollow, max-image-preview: large");
+    tag.add_header_value("unavailable_after: Sun, 06 Nov 1994 08:49:37 GMT, noarchive");
+
+    let general = tag.directives();
+    assert!(general.noindex);
+    assert!(!general.nofollow);
+    assert_eq!(Some(20), general.max_snippet);
+    assert!(general.unavailable_after.is_some());
+    assert!(general.noarchive);
+
+    let google = tag.directives_for("Googlebot");
+    assert!(google.noindex);
+    assert!(google.nofollow);
+    assert_eq!(Some(ImagePreview::Large), google.max_image_preview);
+    assert_eq!(general, tag.directives_for("bingbot"));
+}
+
+#[test]
+fn none_and_meta() {
+    let mut tag = RobotsTag::default();
+    tag.add_meta("robots", "none");
+    tag.add_meta("bingbot", "noarchive");
+    assert!(tag.directives().noindex);
+    assert!(tag.directives().nofollow);
+    assert!(!tag.directives().noarchive);
+    assert!(tag.directives_for("bingbot").noarchive);
+}
@@ -0,0 +1,278 @@
+#![deny(missing_docs)]
+//! Parsing and formatting of HTTP-dates.
+//!
+//! [`parse_http_date`] accepts the three formats allowed by RFC 7231 —
+//! IMF-fixdate, RFC 850 and asctime — and tolerates the deviations seen in the
+//! wild: a missing or wrong weekday, odd spacing, lowercase month names and
+//! `UTC` in place of `GMT`. [`fmt_http_date`] always produces IMF-fixdate.
+
+use std::error;
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const LONG_WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+/// Error returned when a string is not a recognisable HTTP-date.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidHttpDate {
+    input: String,
+}
+
+impl fmt::Display for InvalidHttpDate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid HTTP-date: {:?}", self.input)
+    }
+}
+
+impl error::Error for InvalidHttpDate {}
+
+/// Parses an HTTP-date in IMF-fixdate, RFC 850 or asctime format.
+///
+/// Two-digit years are mapped to 1970–2069.
+pub fn parse_http_date(input: &str) -> Result<SystemTime, InvalidHttpDate> {
+    parse(input).ok_or_else(|| InvalidHttpDate {
+        input: input.to_string(),
+    })
+}
+
+/// Formats `time` as an IMF-fixdate, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`.
+///
+/// Sub-second precision is truncated.
+pub fn fmt_http_date(time: SystemTime) -> String {
+    let seconds = match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_secs() as i64,
+        Err(err) => {
+            let before = err.duration();
+            -(before.as_secs() as i64) - i64::from(before.subsec_nanos() > 0)
+        }
+    };
+    let days = seconds.div_euclid(86_400);
+    let of_day = seconds.rem_euclid(86_400);
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+        WEEKDAYS[(days + 4).rem_euclid(7) as usize],
+        day,
+        MONTHS[month as usize - 1],
+        year,
+        of_day / 3600,
+        of_day / 60 % 60,
+        of_day % 60
+    )
+}
+
+fn parse(input: &str) -> Option<SystemTime> {
+    let mut numbers = Vec::new();
+    let mut month = None;
+    let mut time = None;
+
+    for token in input
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '-')
+        .filter(|token| !token.is_empty())
+    {
+        if token.contains(':') {
+            if time.is_some() {
+                return None;
+            }
+            time = Some(parse_time(token)?);
+        } else if token.bytes().all(|b| b.is_ascii_digit()) {
+            numbers.push(token);
+        } else if let Some(index) = MONTHS
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(token))
+        {
+            if month.is_some() {
+                return None;
+            }
+            month = Some(index as u32 + 1);
+        } else if !is_weekday(token) && !is_utc(token) {
+            return None;
+        }
+    }
+
+    if numbers.len() != 2 {
+        return None;
+    }
+    let day = parse_number(numbers[0], 1, 2)?;
+    let year = match numbers[1].len() {
+        2 => {
+            let year = parse_number(numbers[1], 2, 2)? as i64;
+            if year < 70 {
+                2000 + year
+            } else {
+                1900 + year
+            }
+        }
+        _ => parse_number(numbers[1], 4, 4)? as i64,
+    };
+    let month = month?;
+    let (hour, minute, second) = time?;
+    if day == 0 || day > days_in_month(year, month) {
+        return None;
+    }
+
+    let seconds = days_from_civil(year, month, day) * 86_400
+        + i64::from(hour) * 3600
+        + i64::from(minute) * 60
+        + i64::from(second);
+    if seconds >= 0 {
+        UNIX_EPOCH.checked_add(Duration::from_secs(seconds as u64))
+    } else {
+        UNIX_EPOCH.checked_sub(Duration::from_secs(seconds.unsigned_abs()))
+    }
+}
+
+fn parse_time(token: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = token.split(':');
+    let hour = parse_number(parts.next()?, 1, 2)?;
+    let minute = parse_number(parts.next()?, 2, 2)?;
+    let second = parse_number(parts.next()?, 2, 2)?;
+    if parts.next().is_some() || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    // A leap second is folded into the preceding second.
+    Some((hour, minute, second.min(59)))
+}
+
+fn parse_number(token: &str, min_len: usize, max_len: usize) -> Option<u32> {
+    if token.len() < min_len || token.len() > max_len {
+        return None;
+    }
+    if !token.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+fn is_weekday(token: &str) -> bool {
+    WEEKDAYS
+        .iter()
+        .chain(LONG_WEEKDAYS.iter())
+        .any(|name| name.eq_ignore_ascii_case(token))
+}
+
+fn is_utc(token: &str) -> bool {
+    ["GMT", "UTC", "UT", "Z", "+0000"]
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case(token))
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Days since 1970-01-01 for a proleptic Gregorian date (Howard Hinnant's
+// algorithm), and its inverse.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[test]
+fn parse_rfc7231_formats() {
+    let expected = UNIX_EPOCH + Duration::from_secs(784_111_777);
+    assert_eq!(
+        Ok(expected),
+        parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT")
+    );
+    assert_eq!(
+        Ok(expected),
+        parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT")
+    );
+    assert_eq!(Ok(expected), parse_http_date("Sun Nov  6 08:49:37 1994"));
+}
+
+#[test]
+fn parse_tolerates_common_deviations() {
+    let expected = UNIX_EPOCH + Duration::from_secs(784_111_777);
+    assert_eq!(Ok(expected), parse_http_date("06 Nov 1994 08:49:37 GMT"));
+    assert_eq!(
+        Ok(expected),
+        parse_http_date("Mon, 06 Nov 1994 08:49:37 GMT")
+    );
+    assert_eq!(
+        Ok(expected),
+        parse_http_date(" sun,  06 nov 1994 08:49:37 UTC ")
+    );
+    assert_eq!(Ok(expected), parse_http_date("Sun, 6 Nov 1994 8:49:37 GMT"));
+}
+
+#[test]
+fn parse_rejects_garbage() {
+    assert!(parse_http_date("").is_err());
+    assert!(parse_http_date("yesterday").is_err());
+    assert!(parse_http_date("Sun, 31 Feb 1994 08:49:37 GMT").is_err());
+    assert!(parse_http_date("Sun, 06 Nov 1994 24:00:00 GMT").is_err());
+    assert!(parse_http_date("Sun, 06 Nov 1994 08:49:37 PST").is_err());
+    assert!(parse_http_date("Sun, 06 Nov 1994 GMT").is_err());
+}
+
+#[test]
+fn parse_years_around_epoch() {
+    assert_eq!(
+        Ok(UNIX_EPOCH),
+        parse_http_date("Thursday, 01-Jan-70 00:00:00 GMT")
+    );
+    assert_eq!(
+        Ok(UNIX_EPOCH - Duration::from_secs(1)),
+        parse_http_date("Wed, 31 Dec 1969 23:59:59 GMT")
+    );
+    assert_eq!(
+        Ok(UNIX_EPOCH + Duration::from_secs(3_124_224_000)),
+        parse_http_date("Saturday, 01-Jan-69 00:00:00 GMT")
+    );
+}
+
+#[test]
+fn format_round_trip() {
+    let time = UNIX_EPOCH + Duration::from_secs(784_111_777);
+    assert_eq!("Sun, 06 Nov 1994 08:49:37 GMT", fmt_http_date(time));
+    assert_eq!("Thu, 01 Jan 1970 00:00:00 GMT", fmt_http_date(UNIX_EPOCH));
+    assert_eq!(
+        "Wed, 31 Dec 1969 23:59:59 GMT",
+        fmt_http_date(UNIX_EPOCH - Duration::from_secs(1))
+    );
+    let leap_day = "Thu, 29 Feb 2024 12:00:00 GMT";
+    assert_eq!(leap_day, fmt_http_date(parse_http_date(leap_day).unwrap()));
+}
@@ -1,5 +1,43 @@
 #![deny(missing_docs)]
 //! glass-fi
+extern crate bytes;
+extern crate encoding_rs;
 extern crate url;
 extern crate tokio;
+#[cfg(feature = "json")]
+extern crate serde;
+#[cfg(feature = "json")]
+extern crate serde_json;
+#[cfg(feature = "https")]
+extern crate tokio_rustls;
+#[cfg(feature = "https")]
+extern crate webpki_roots;
+#[cfg(any(feature = "https", feature = "native-tls"))]
+extern crate sha2;
+#[cfg(feature = "native-tls")]
+extern crate native_tls;
+#[cfg(feature = "native-tls")]
+extern crate tokio_tls;
+
+#[cfg(all(feature = "https", feature = "native-tls"))]
+compile_error!(
+    "the `https` and `native-tls` features select different TLS backends; enable only one"
+);
+
 mod client;
+#[cfg(feature = "crawl")]
+pub mod crawl;
+pub mod httpdate;
+#[cfg(feature = "loadgen")]
+pub mod loadgen;
+mod proto;
+
+#[cfg(any(feature = "https", feature = "native-tls"))]
+pub use client::{Certificate, Identity, TlsInfo, TlsVersion};
+pub use client::{
+    Body, BodyStream, BrowserProfile, ClientBuilder, ClientRegistry, Forwarded, HeaderMap,
+    HttpBody, HttpHeader, HttpHeaders, HttpResponse, HttpResponseError, IntoUrl, Method, ParseMode,
+    Phase, ProtocolWarning, RedirectAction, RedirectAttempt, RedirectHook, RedirectPolicy,
+    RequestBuilder, RequestProfile, SimpleClient, StatusClass, StatusCode, StreamingResponse,
+    TransferSizes, UrlBuilder, UrlError,
+};
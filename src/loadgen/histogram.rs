@@ -0,0 +1,182 @@
+#![deny(missing_docs)]
+
+use std::cmp;
+use std::time::Duration;
+
+// Values below `2 * SUB_BUCKETS` microseconds are counted exactly; above that
+// every power of two is split into `SUB_BUCKETS` equal buckets, which keeps
+// the relative error under 1/64.
+const SUB_BUCKET_BITS: u32 = 6;
+const SUB_BUCKETS: u64 = 1 << SUB_BUCKET_BITS;
+
+/// A latency histogram with microsecond resolution and bounded relative error.
+///
+/// Buckets are log-linear in the style of HdrHistogram, so memory grows with
+/// the logarithm of the largest value rather than with the number of samples.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Histogram {
+    counts: Vec<u64>,
+    len: u64,
+    min: u64,
+    max: u64,
+    sum: u128,
+}
+
+impl Histogram {
+    /// Creates an empty histogram.
+    pub fn new() -> Self {
+        Histogram::default()
+    }
+
+    /// Records one sample.
+    pub fn record(&mut self, latency: Duration) {
+        let micros = cmp::min(latency.as_micros(), u128::from(u64::MAX)) as u64;
+        let index = bucket_index(micros);
+        if self.counts.len() <= index {
+            self.counts.resize(index + 1, 0);
+        }
+        self.counts[index] += 1;
+        self.min = if self.len == 0 {
+            micros
+        } else {
+            cmp::min(self.min, micros)
+        };
+        self.max = cmp::max(self.max, micros);
+        self.len += 1;
+        self.sum += u128::from(micros);
+    }
+
+    /// Adds every sample of `other` to this histogram.
+    pub fn merge(&mut self, other: &Histogram) {
+        if other.len == 0 {
+            return;
+        }
+        if self.counts.len() < other.counts.len() {
+            self.counts.resize(other.counts.len(), 0);
+        }
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+        }
+        self.min = if self.len == 0 {
+            other.min
+        } else {
+            cmp::min(self.min, other.min)
+        };
+        self.max = cmp::max(self.max, other.max);
+        self.len += other.len;
+        self.sum += other.sum;
+    }
+
+    /// Returns the number of recorded samples.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns `true` if nothing has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the smallest sample, exactly.
+    pub fn min(&self) -> Duration {
+        Duration::from_micros(self.min)
+    }
+
+    /// Returns the largest sample, exactly.
+    pub fn max(&self) -> Duration {
+        Duration::from_micros(self.max)
+    }
+
+    /// Returns the arithmetic mean of the samples.
+    pub fn mean(&self) -> Duration {
+        if self.len == 0 {
+            return Duration::from_micros(0);
+        }
+        Duration::from_micros((self.sum / u128::from(self.len)) as u64)
+    }
+
+    /// Returns the sample at `quantile`, e.g. `0.99` for the 99th percentile.
+    ///
+    /// The result is the upper bound of the bucket holding that sample,
+    /// clamped to the largest sample. Quantiles outside `0.0..=1.0` are
+    /// clamped too.
+    pub fn value_at_quantile(&self, quantile: f64) -> Duration {
+        if self.len == 0 {
+            return Duration::from_micros(0);
+        }
+        let quantile = quantile.clamp(0.0, 1.0);
+        let rank = cmp::max(1, (quantile * self.len as f64).ceil() as u64);
+        let mut seen = 0;
+        for (index, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                let value = cmp::min(bucket_highest(index), self.max);
+                return Duration::from_micros(cmp::max(value, self.min));
+            }
+        }
+        self.max()
+    }
+}
+
+fn bucket_index(value: u64) -> usize {
+    if value < 2 * SUB_BUCKETS {
+        return value as usize;
+    }
+    let shift = 64 - value.leading_zeros() - SUB_BUCKET_BITS - 1;
+    let top = value >> shift;
+    (2 * SUB_BUCKETS + u64::from(shift - 1) * SUB_BUCKETS + (top - SUB_BUCKETS)) as usize
+}
+
+fn bucket_highest(index: usize) -> u64 {
+    let index = index as u64;
+    if index < 2 * SUB_BUCKETS {
+        return index;
+    }
+    let shift = (index - 2 * SUB_BUCKETS) / SUB_BUCKETS + 1;
+    let top = (index - 2 * SUB_BUCKETS) % SUB_BUCKETS + SUB_BUCKETS;
+    (top << shift).saturating_add((1 << shift) - 1)
+}
+
+#[test]
+fn buckets_bound_relative_error() {
+    for &value in &[0, 1, 127, 128, 129, 1000, 65_535, 1_000_000, u64::MAX / 2] {
+        let index = bucket_index(value);
+        let highest = bucket_highest(index);
+        assert!(highest >= value, "{} -> {}", value, highest);
+        assert!(
+            highest - value <= value / SUB_BUCKETS,
+            "{} -> {}",
+            value,
+            highest
+        );
+        if index > 0 {
+            assert!(bucket_highest(index - 1) < value);
+        }
+    }
+}
+
+#[test]
+fn quantiles_and_merge() {
+    let mut fast = Histogram::new();
+    for micros in 1..=90 {
+        fast.record(Duration::from_micros(micros));
+    }
+    let mut slow = Histogram::new();
+    for millis in 1..=10 {
+        slow.record(Duration::from_millis(millis * 10));
+    }
+    fast.merge(&slow);
+
+    assert_eq!(100, fast.len());
+    assert_eq!(Duration::from_micros(1), fast.min());
+    assert_eq!(Duration::from_millis(100), fast.max());
+    assert_eq!(Duration::from_micros(50), fast.value_at_quantile(0.5));
+    assert_eq!(Duration::from_micros(90), fast.value_at_quantile(0.9));
+    let p95 = fast.value_at_quantile(0.95).as_micros();
+    assert!((50_000..=50_000 + 50_000 / 64).contains(&p95), "{}", p95);
+    assert_eq!(Duration::from_millis(100), fast.value_at_quantile(1.0));
+    assert_eq!(
+        Duration::from_micros(0),
+        Histogram::new().value_at_quantile(0.5)
+    );
+}
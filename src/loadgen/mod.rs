@@ -0,0 +1,222 @@
+#![deny(missing_docs)]
+//! Load generation, enabled by the `loadgen` feature.
+//!
+//! A `LoadGenerator` sends requests built from a template on one of two
+//! schedules and collects their latencies into a `Histogram`:
+//!
+//! * `Schedule::OpenLoop` starts requests at a fixed rate whether or not
+//!   earlier ones have finished. Latency is measured from the moment a
+//!   request was due, so a stalled server shows up in the tail instead of
+//!   silently lowering the rate.
+//! * `Schedule::ClosedLoop` keeps a fixed number of requests in flight, each
+//!   worker sending the next one as soon as the previous one completes.
+mod histogram;
+
+pub use self::histogram::Histogram;
+
+use std::cmp;
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use client::{HttpResponseError, RequestBuilder, SimpleClient};
+
+/// How requests are scheduled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Schedule {
+    /// Start `rate` requests per second, independent of response times.
+    OpenLoop {
+        /// Requests per second.
+        rate: f64,
+    },
+    /// Keep `concurrency` requests in flight.
+    ClosedLoop {
+        /// Number of concurrent workers.
+        concurrency: usize,
+    },
+}
+
+/// Outcome of a load-generation run.
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    /// Latencies of requests that produced a response, of any status.
+    pub latency: Histogram,
+    /// Number of requests that produced a response.
+    pub succeeded: u64,
+    /// Number of requests that failed with an error.
+    pub failed: u64,
+    /// Number of responses with a 4xx or 5xx status, included in `succeeded`.
+    pub error_statuses: u64,
+    /// Wall-clock time from the first request being due to the last one finishing.
+    pub elapsed: Duration,
+}
+
+impl Report {
+    /// Returns the number of requests sent.
+    pub fn sent(&self) -> u64 {
+        self.succeeded + self.failed
+    }
+
+    /// Returns the achieved throughput in requests per second.
+    pub fn throughput(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds == 0.0 {
+            0.0
+        } else {
+            self.sent() as f64 / seconds
+        }
+    }
+
+    fn merge(&mut self, other: &Report) {
+        self.latency.merge(&other.latency);
+        self.succeeded += other.succeeded;
+        self.failed += other.failed;
+        self.error_statuses += other.error_statuses;
+    }
+
+    fn record(&mut self, due: Instant, result: Result<u16, HttpResponseError>) {
+        match result {
+            Ok(status) => {
+                self.latency.record(due.elapsed());
+                self.succeeded += 1;
+                if status >= 400 {
+                    self.error_statuses += 1;
+                }
+            }
+            Err(_) => self.failed += 1,
+        }
+    }
+}
+
+/// Drives requests built by a template against a server.
+pub struct LoadGenerator<F> {
+    client: SimpleClient,
+    template: F,
+    schedule: Schedule,
+    duration: Duration,
+    max_in_flight: usize,
+}
+
+impl<F> LoadGenerator<F>
+where
+    F: Fn(&SimpleClient) -> RequestBuilder + Sync,
+{
+    /// Creates a generator that builds each request with `template`.
+    ///
+    /// Defaults to a closed loop with one worker for ten seconds.
+    pub fn new(client: SimpleClient, template: F) -> Self {
+        LoadGenerator {
+            client,
+            template,
+            schedule: Schedule::ClosedLoop { concurrency: 1 },
+            duration: Duration::from_secs(10),
+            max_in_flight: 64,
+        }
+    }
+
+    /// Sets the schedule.
+    pub fn schedule(mut self, schedule: Schedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// Sets how long requests keep being started.
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Caps the number of worker threads of an open loop. Requests that are
+    /// due while every worker is busy wait, and the wait counts as latency.
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = cmp::max(1, max_in_flight);
+        self
+    }
+
+    /// Runs the schedule to completion and returns the collected report.
+    ///
+    /// Requests still in flight when `duration` ends are waited for.
+    pub fn run(&self) -> Report {
+        let started = Instant::now();
+        let mut report = match self.schedule {
+            Schedule::OpenLoop { rate } => self.run_open(started, rate),
+            Schedule::ClosedLoop { concurrency } => self.run_closed(started, concurrency),
+        };
+        report.elapsed = started.elapsed();
+        report
+    }
+
+    fn send(&self) -> Result<u16, HttpResponseError> {
+        (self.template)(&self.client)
+            .send()
+            .map(|response| response.status.as_u16())
+    }
+
+    fn run_open(&self, started: Instant, rate: f64) -> Report {
+        if rate <= 0.0 || !rate.is_finite() {
+            return Report::default();
+        }
+        let interval = Duration::from_secs_f64(1.0 / rate);
+        let (sender, receiver) = mpsc::channel::<Instant>();
+        let receiver = Mutex::new(receiver);
+        let receiver = &receiver;
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.max_in_flight)
+                .map(|_| {
+                    scope.spawn(move || {
+                        let mut report = Report::default();
+                        loop {
+                            let due = match receiver.lock().unwrap().recv() {
+                                Ok(due) => due,
+                                Err(_) => return report,
+                            };
+                            report.record(due, self.send());
+                        }
+                    })
+                })
+                .collect();
+
+            let mut due = started;
+            while due < started + self.duration {
+                let now = Instant::now();
+                if due > now {
+                    thread::sleep(due - now);
+                }
+                if sender.send(due).is_err() {
+                    break;
+                }
+                due += interval;
+            }
+            drop(sender);
+            collect(workers)
+        })
+    }
+
+    fn run_closed(&self, started: Instant, concurrency: usize) -> Report {
+        let deadline = started + self.duration;
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..cmp::max(1, concurrency))
+                .map(|_| {
+                    scope.spawn(move || {
+                        let mut report = Report::default();
+                        while Instant::now() < deadline {
+                            let sent = Instant::now();
+                            report.record(sent, self.send());
+                        }
+                        report
+                    })
+                })
+                .collect();
+            collect(workers)
+        })
+    }
+}
+
+fn collect(workers: Vec<thread::ScopedJoinHandle<'_, Report>>) -> Report {
+    let mut total = Report::default();
+    for worker in workers {
+        total.merge(&worker.join().expect("load generator worker panicked"));
+    }
+    total
+}
@@ -0,0 +1,267 @@
+#![deny(missing_docs)]
+
+use client::{HeaderMap, HttpResponseError, Method, ParseMode, ProtocolWarning, StatusCode};
+
+/// How the end of a response body is found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyFraming {
+    /// The response has no body.
+    Empty,
+    /// The body is exactly this many bytes.
+    Length(u64),
+    /// The body uses chunked transfer coding.
+    Chunked,
+    /// The body runs until the server closes the connection.
+    CloseDelimited,
+}
+
+/// Determines the framing of a response to a `method` request, following
+/// RFC 7230 section 3.3.3.
+///
+/// Responses to `HEAD` and those whose status forbids content, 1xx, 204, 205
+/// and 304, have no body whatever their headers say. A 205 that announces
+/// content anyway, with `Transfer-Encoding` or a non-zero `Content-Length`,
+/// is a deviation as below. Otherwise `Transfer-Encoding` wins over
+/// `Content-Length`; a body with neither is read until the connection closes.
+///
+/// Repeated `Content-Length` headers that agree, and `Content-Length` sent
+/// next to `Transfer-Encoding`, are pushed to `warnings` in lenient mode and
+/// rejected in strict mode. `Content-Length` headers that disagree are always
+/// rejected.
+pub fn response_body_framing(
+    method: &Method,
+    status: StatusCode,
+    headers: &HeaderMap,
+    mode: ParseMode,
+    warnings: &mut Vec<ProtocolWarning>,
+) -> Result<BodyFraming, HttpResponseError> {
+    let length = check_framing_headers(headers, mode, warnings)?;
+    if status.as_u16() == 205
+        && (headers.contains_key("Transfer-Encoding") || length.is_some_and(|value| value != "0"))
+    {
+        warn(mode, warnings, ProtocolWarning::ContentWithResetContent)?;
+    }
+    if *method == Method::HEAD || status.forbids_content() {
+        return Ok(BodyFraming::Empty);
+    }
+    if headers.contains_key("Transfer-Encoding") {
+        let last = headers
+            .get_all("Transfer-Encoding")
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .filter(|coding| !coding.is_empty())
+            .last();
+        return Ok(match last {
+            Some(coding) if coding.eq_ignore_ascii_case("chunked") => BodyFraming::Chunked,
+            _ => BodyFraming::CloseDelimited,
+        });
+    }
+    match length {
+        Some(value) => value
+            .parse::<u64>()
+            .map(BodyFraming::Length)
+            .map_err(|_| HttpResponseError::InvalidContentLength(value.to_string())),
+        None => Ok(BodyFraming::CloseDelimited),
+    }
+}
+
+// Looks for the header combinations that let two parsers disagree on where
+// the body ends, and returns the `Content-Length` value if there is one.
+fn check_framing_headers<'a>(
+    headers: &'a HeaderMap,
+    mode: ParseMode,
+    warnings: &mut Vec<ProtocolWarning>,
+) -> Result<Option<&'a str>, HttpResponseError> {
+    let mut lengths = headers.get_all("Content-Length");
+    let value = match lengths.next() {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+    let mut duplicated = false;
+    for other in lengths {
+        if other != value {
+            return Err(HttpResponseError::InvalidContentLength(format!(
+                "{}, {}",
+                value, other
+            )));
+        }
+        duplicated = true;
+    }
+    if duplicated {
+        warn(mode, warnings, ProtocolWarning::DuplicateContentLength)?;
+    }
+    if headers.contains_key("Transfer-Encoding") {
+        warn(
+            mode,
+            warnings,
+            ProtocolWarning::ContentLengthWithTransferEncoding,
+        )?;
+    }
+    Ok(Some(value))
+}
+
+fn warn(
+    mode: ParseMode,
+    warnings: &mut Vec<ProtocolWarning>,
+    warning: ProtocolWarning,
+) -> Result<(), HttpResponseError> {
+    match mode {
+        ParseMode::Strict => Err(HttpResponseError::ProtocolViolation(warning.to_string())),
+        ParseMode::Lenient => {
+            warnings.push(warning);
+            Ok(())
+        }
+    }
+}
+
+/// Parses the size from a chunk-size line, ignoring any chunk extensions.
+pub fn parse_chunk_size(line: &str) -> Result<u64, HttpResponseError> {
+    let size = line.split(';').next().unwrap_or("").trim();
+    if size.is_empty() || size.len() > 16 || !size.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(HttpResponseError::InvalidChunk(line.to_string()));
+    }
+    u64::from_str_radix(size, 16).map_err(|_| HttpResponseError::InvalidChunk(line.to_string()))
+}
+
+#[test]
+fn framing_follows_rfc7230() {
+    let framing = |method: &Method, code: u16, headers: &[(&str, &str)]| {
+        let mut map = HeaderMap::new();
+        for &(name, value) in headers {
+            map.append(name, value);
+        }
+        response_body_framing(
+            method,
+            StatusCode::from_u16(code).unwrap(),
+            &map,
+            ParseMode::Lenient,
+            &mut Vec::new(),
+        )
+    };
+    let get = &Method::GET;
+    assert_eq!(
+        BodyFraming::Empty,
+        framing(&Method::HEAD, 200, &[("Content-Length", "5")]).unwrap()
+    );
+    assert_eq!(
+        BodyFraming::Empty,
+        framing(get, 304, &[("Content-Length", "5")]).unwrap()
+    );
+    assert_eq!(BodyFraming::Empty, framing(get, 101, &[]).unwrap());
+    assert_eq!(
+        BodyFraming::Chunked,
+        framing(
+            get,
+            200,
+            &[
+                ("Transfer-Encoding", "gzip"),
+                ("transfer-encoding", "Chunked"),
+                ("Content-Length", "5")
+            ]
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        BodyFraming::CloseDelimited,
+        framing(get, 200, &[("Transfer-Encoding", "chunked, gzip")]).unwrap()
+    );
+    assert_eq!(
+        BodyFraming::Length(5),
+        framing(get, 200, &[("Content-Length", "5")]).unwrap()
+    );
+    assert_eq!(BodyFraming::CloseDelimited, framing(get, 200, &[]).unwrap());
+    assert!(framing(get, 200, &[("Content-Length", "-1")]).is_err());
+}
+
+#[test]
+fn ambiguous_framing_headers() {
+    let ok = StatusCode::from_u16(200).unwrap();
+    let framing = |headers: &HeaderMap, mode: ParseMode| {
+        let mut warnings = Vec::new();
+        response_body_framing(&Method::GET, ok, headers, mode, &mut warnings)
+            .map(|framing| (framing, warnings))
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.append("Content-Length", "5");
+    headers.append("content-length", "5");
+    assert_eq!(
+        (
+            BodyFraming::Length(5),
+            vec![ProtocolWarning::DuplicateContentLength]
+        ),
+        framing(&headers, ParseMode::Lenient).unwrap()
+    );
+    match framing(&headers, ParseMode::Strict) {
+        Err(HttpResponseError::ProtocolViolation(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    headers.append("Transfer-Encoding", "chunked");
+    assert_eq!(
+        (
+            BodyFraming::Chunked,
+            vec![
+                ProtocolWarning::DuplicateContentLength,
+                ProtocolWarning::ContentLengthWithTransferEncoding
+            ]
+        ),
+        framing(&headers, ParseMode::Lenient).unwrap()
+    );
+
+    headers.append("Content-Length", "6");
+    match framing(&headers, ParseMode::Lenient) {
+        Err(HttpResponseError::InvalidContentLength(ref value)) => assert_eq!("5, 6", value),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn reset_content_has_no_body() {
+    let reset = StatusCode::from_u16(205).unwrap();
+    let framing = |headers: &[(&str, &str)], mode: ParseMode| {
+        let mut map = HeaderMap::new();
+        for &(name, value) in headers {
+            map.append(name, value);
+        }
+        let mut warnings = Vec::new();
+        response_body_framing(&Method::POST, reset, &map, mode, &mut warnings)
+            .map(|framing| (framing, warnings))
+    };
+    assert_eq!(
+        (BodyFraming::Empty, vec![]),
+        framing(&[], ParseMode::Strict).unwrap()
+    );
+    assert_eq!(
+        (BodyFraming::Empty, vec![]),
+        framing(&[("Content-Length", "0")], ParseMode::Strict).unwrap()
+    );
+    for headers in &[
+        &[("Content-Length", "3")][..],
+        &[("Transfer-Encoding", "chunked")][..],
+    ] {
+        assert_eq!(
+            (
+                BodyFraming::Empty,
+                vec![ProtocolWarning::ContentWithResetContent]
+            ),
+            framing(headers, ParseMode::Lenient).unwrap()
+        );
+        match framing(headers, ParseMode::Strict) {
+            Err(HttpResponseError::ProtocolViolation(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn chunk_sizes() {
+    assert_eq!(0, parse_chunk_size("0").unwrap());
+    assert_eq!(0x1a, parse_chunk_size("1A").unwrap());
+    assert_eq!(0x10, parse_chunk_size("10 ; name=value").unwrap());
+    assert_eq!(u64::MAX, parse_chunk_size("ffffffffffffffff").unwrap());
+    assert!(parse_chunk_size("").is_err());
+    assert!(parse_chunk_size("0x10").is_err());
+    assert!(parse_chunk_size("-1").is_err());
+    assert!(parse_chunk_size("10000000000000000").is_err());
+}
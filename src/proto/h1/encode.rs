@@ -0,0 +1,229 @@
+#![deny(missing_docs)]
+
+use client::{HeaderMap, Method};
+#[cfg(test)]
+use client::HttpHeader;
+
+/// How a request body is delimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestFraming {
+    /// `Content-Length` of this many bytes.
+    Length(u64),
+    /// `Transfer-Encoding: chunked`, see `encode_chunk`.
+    Chunked,
+}
+
+/// The zero-length chunk that ends a chunked body, with no trailers.
+pub const LAST_CHUNK: &[u8] = b"0\r\n\r\n";
+
+/// Serializes an HTTP/1.1 request line and header block, ending with the
+/// blank line that separates it from the body.
+///
+/// `target` is written as is and must already be in origin form, e.g.
+/// `/search?q=rust`. The `Content-Length` or `Transfer-Encoding` header for
+/// `framing` is added and replaces any of either in `headers`, so the framing
+/// always matches the body actually sent; without a body, both are dropped.
+/// Names must already be valid tokens and values must not contain line
+/// breaks; `RequestBuilder` checks both.
+///
+/// Headers are written in order with their names as given. The framing
+/// header goes last, unless `preserve_order` is set and `headers` already has
+/// one of the same name: then the first of those keeps its place and
+/// spelling and only its value is replaced.
+pub fn encode_request_head(
+    method: &Method,
+    target: &str,
+    headers: &HeaderMap,
+    framing: Option<RequestFraming>,
+    preserve_order: bool,
+) -> Vec<u8> {
+    let mut head = Vec::with_capacity(256);
+    push_line(&mut head, &[method.as_str(), " ", target, " HTTP/1.1"]);
+    let framing = framing.map(|framing| match framing {
+        RequestFraming::Length(length) => ("Content-Length", length.to_string()),
+        RequestFraming::Chunked => ("Transfer-Encoding", "chunked".to_string()),
+    });
+    let mut framing_written = false;
+    for (name, value) in headers {
+        if name.eq_ignore_ascii_case("Content-Length")
+            || name.eq_ignore_ascii_case("Transfer-Encoding")
+        {
+            if let Some((framing_name, ref framing_value)) = framing {
+                if preserve_order && !framing_written && name.eq_ignore_ascii_case(framing_name) {
+                    push_line(&mut head, &[name, ": ", framing_value]);
+                    framing_written = true;
+                }
+            }
+            continue;
+        }
+        push_line(&mut head, &[name, ": ", value]);
+    }
+    if let Some((name, ref value)) = framing {
+        if !framing_written {
+            push_line(&mut head, &[name, ": ", value]);
+        }
+    }
+    head.extend_from_slice(b"\r\n");
+    head
+}
+
+/// Serializes one chunk of a chunked body. `data` must not be empty, as an
+/// empty chunk would end the body; see `LAST_CHUNK`.
+pub fn encode_chunk(data: &[u8]) -> Vec<u8> {
+    let mut chunk = format!("{:x}\r\n", data.len()).into_bytes();
+    chunk.extend_from_slice(data);
+    chunk.extend_from_slice(b"\r\n");
+    chunk
+}
+
+/// Serializes the zero-length chunk that ends a chunked body, followed by
+/// `trailers`. Names and values must be valid, as for
+/// `encode_request_head`.
+pub fn encode_last_chunk(trailers: &HeaderMap) -> Vec<u8> {
+    let mut last = b"0\r\n".to_vec();
+    for (name, value) in trailers {
+        push_line(&mut last, &[name, ": ", value]);
+    }
+    last.extend_from_slice(b"\r\n");
+    last
+}
+
+fn push_line(head: &mut Vec<u8>, parts: &[&str]) {
+    for part in parts {
+        head.extend_from_slice(part.as_bytes());
+    }
+    head.extend_from_slice(b"\r\n");
+}
+
+#[test]
+fn encode_get() {
+    let mut headers = HeaderMap::new();
+    headers.append("Host", "example.com:8080");
+    headers.append("Accept", "*/*");
+    assert_eq!(
+        &b"GET /search?q=rust HTTP/1.1\r\n\
+           Host: example.com:8080\r\n\
+           Accept: */*\r\n\
+           \r\n"[..],
+        &encode_request_head(&Method::GET, "/search?q=rust", &headers, None, false)[..]
+    );
+}
+
+#[test]
+fn encode_body_length() {
+    let mut headers = HeaderMap::new();
+    headers.append("Host", "example.com");
+    headers.append("content-length", "999");
+    headers.append("X-Trace", "1");
+    headers.append("X-Trace", "2");
+    assert_eq!(
+        &b"POST /upload HTTP/1.1\r\n\
+           Host: example.com\r\n\
+           X-Trace: 1\r\n\
+           X-Trace: 2\r\n\
+           Content-Length: 7\r\n\
+           \r\n"[..],
+        &encode_request_head(
+            &Method::POST,
+            "/upload",
+            &headers,
+            Some(RequestFraming::Length(7)),
+            false
+        )[..]
+    );
+    assert_eq!(
+        &b"PUT / HTTP/1.1\r\nContent-Length: 0\r\n\r\n"[..],
+        &encode_request_head(
+            &Method::PUT,
+            "/",
+            &HeaderMap::new(),
+            Some(RequestFraming::Length(0)),
+            false
+        )[..]
+    );
+}
+
+#[test]
+fn encode_preserving_order() {
+    let mut headers = HeaderMap::new();
+    headers.append("host", "example.com");
+    headers.append("content-length", "999");
+    headers.append("X-Trace", "1");
+    headers.append("CONTENT-LENGTH", "998");
+    assert_eq!(
+        &b"POST /upload HTTP/1.1\r\n\
+           host: example.com\r\n\
+           content-length: 7\r\n\
+           X-Trace: 1\r\n\
+           \r\n"[..],
+        &encode_request_head(
+            &Method::POST,
+            "/upload",
+            &headers,
+            Some(RequestFraming::Length(7)),
+            true
+        )[..]
+    );
+    assert_eq!(
+        &b"PUT / HTTP/1.1\r\nX-Trace: 1\r\nContent-Length: 0\r\n\r\n"[..],
+        &encode_request_head(
+            &Method::PUT,
+            "/",
+            &HeaderMap::from(vec![HttpHeader {
+                name: "X-Trace".to_string(),
+                content: "1".to_string(),
+            }]),
+            Some(RequestFraming::Length(0)),
+            true
+        )[..]
+    );
+}
+
+#[test]
+fn encode_without_body_drops_framing_headers() {
+    let mut headers = HeaderMap::new();
+    headers.append("Host", "example.com");
+    headers.append("Transfer-Encoding", "chunked");
+    headers.append("content-length", "10");
+    headers.append("Accept", "*/*");
+    for &preserve_order in &[false, true] {
+        let head = encode_request_head(&Method::GET, "/", &headers, None, preserve_order);
+        assert_eq!(
+            &b"GET / HTTP/1.1\r\nHost: example.com\r\nAccept: */*\r\n\r\n"[..],
+            &head[..]
+        );
+    }
+}
+
+#[test]
+fn encode_chunked() {
+    let mut headers = HeaderMap::new();
+    headers.append("Transfer-Encoding", "gzip");
+    headers.append("Content-Length", "3");
+    headers.append("X-Trace", "1");
+    assert_eq!(
+        &b"POST / HTTP/1.1\r\n\
+           X-Trace: 1\r\n\
+           Transfer-Encoding: chunked\r\n\
+           \r\n"[..],
+        &encode_request_head(
+            &Method::POST,
+            "/",
+            &headers,
+            Some(RequestFraming::Chunked),
+            false
+        )[..]
+    );
+    assert_eq!(
+        &b"1a\r\nabcdefghijklmnopqrstuvwxyz\r\n"[..],
+        &encode_chunk(b"abcdefghijklmnopqrstuvwxyz")[..]
+    );
+    assert_eq!(LAST_CHUNK, &encode_last_chunk(&HeaderMap::new())[..]);
+    let mut trailers = HeaderMap::new();
+    trailers.append("Digest", "sha-256=abc");
+    trailers.append("X-Rows", "3");
+    assert_eq!(
+        &b"0\r\nDigest: sha-256=abc\r\nX-Rows: 3\r\n\r\n"[..],
+        &encode_last_chunk(&trailers)[..]
+    );
+}
@@ -0,0 +1,9 @@
+#![deny(missing_docs)]
+//! HTTP/1.1 message framing.
+mod decode;
+mod encode;
+
+pub use self::decode::{parse_chunk_size, response_body_framing, BodyFraming};
+pub use self::encode::{
+    encode_chunk, encode_last_chunk, encode_request_head, RequestFraming, LAST_CHUNK,
+};
@@ -0,0 +1,3 @@
+#![deny(missing_docs)]
+//! Wire formats spoken by the client.
+pub mod h1;
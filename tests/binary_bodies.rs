@@ -0,0 +1,41 @@
+extern crate glass_fi;
+
+mod support;
+
+use glass_fi::SimpleClient;
+
+#[test]
+fn binary_body_is_kept_exactly() {
+    let url = support::serve_once(
+        b"HTTP/1.1 200 OK\r\nContent-Type: application/octet-stream\r\nContent-Length: 10\r\n\r\n\
+          \x89PNG\r\n\x00\xff\n\r",
+    );
+    let response = SimpleClient::new().get(url).unwrap();
+    assert_eq!(&b"\x89PNG\r\n\x00\xff\n\r"[..], &response.bytes()[..]);
+    assert_eq!(10, response.transfer_sizes().body_bytes_received);
+    assert_eq!("\u{fffd}PNG\r\n\0\u{fffd}\n\r", response.text());
+    assert_eq!(response.text(), response.body.text);
+}
+
+#[test]
+fn binary_data_url() {
+    let response = SimpleClient::new()
+        .get("data:application/octet-stream;base64,AP8Q")
+        .unwrap();
+    assert_eq!(&[0x00, 0xff, 0x10][..], &response.bytes()[..]);
+}
+
+#[test]
+fn text_follows_the_declared_charset() {
+    let url = support::serve_once(
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=Shift_JIS\r\nContent-Length: 4\r\n\r\n\
+          \x93\xfa\x96\x7b",
+    );
+    let response = SimpleClient::new().get(url).unwrap();
+    assert_eq!("日本", response.text());
+    assert_eq!("日本", response.body.text);
+    assert_eq!(
+        "\u{201c}\u{fa}\u{2013}{",
+        response.text_with_charset("windows-1252")
+    );
+}
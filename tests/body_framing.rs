@@ -0,0 +1,72 @@
+extern crate glass_fi;
+
+mod support;
+
+use glass_fi::{HttpResponseError, SimpleClient};
+
+#[test]
+fn chunked_body_is_decoded() {
+    let url = support::serve_once(
+        b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+          5\r\nhello\r\n\
+          7;ext=1\r\n, world\r\n\
+          0\r\nX-Checksum: abc\r\n\r\n",
+    );
+    let response = SimpleClient::new().get(url).unwrap();
+    assert_eq!("hello, world", response.text());
+    assert_eq!(50, response.transfer_sizes().body_bytes_received);
+}
+
+#[test]
+fn chunked_wins_over_content_length() {
+    let url = support::serve_once(
+        b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nTransfer-Encoding: chunked\r\n\r\n\
+          3\r\nabc\r\n0\r\n\r\n",
+    );
+    assert_eq!("abc", SimpleClient::new().get(url).unwrap().text());
+}
+
+#[test]
+fn body_without_length_runs_to_eof() {
+    let url =
+        support::serve_once(b"HTTP/1.0 200 OK\r\nContent-Type: text/plain\r\n\r\nuntil\r\nclose");
+    let response = SimpleClient::new().get(url).unwrap();
+    assert_eq!("until\r\nclose", response.text());
+    assert_eq!(12, response.transfer_sizes().body_bytes_received);
+
+    let url = support::serve_once(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: gzip\r\n\r\nraw");
+    assert_eq!(
+        &b"raw"[..],
+        &SimpleClient::new().get(url).unwrap().bytes()[..]
+    );
+}
+
+#[test]
+fn no_content_has_no_body() {
+    let url = support::serve_once(b"HTTP/1.1 204 No Content\r\nContent-Length: 5\r\n\r\nhello");
+    assert!(SimpleClient::new().get(url).unwrap().bytes().is_empty());
+}
+
+#[test]
+fn malformed_chunks() {
+    let url = support::serve_once(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nzz\r\n");
+    match SimpleClient::new().get(url) {
+        Err(HttpResponseError::InvalidChunk(ref line)) => assert_eq!("zz", line),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let url = support::serve_once(
+        b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabcdef\r\n0\r\n\r\n",
+    );
+    match SimpleClient::new().get(url) {
+        Err(HttpResponseError::InvalidChunk(ref line)) => assert_eq!("def", line),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let url =
+        support::serve_once(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n10\r\nshort");
+    match SimpleClient::new().get(url) {
+        Err(ref err @ HttpResponseError::Io(_)) => assert!(err.is_retryable()),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
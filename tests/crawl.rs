@@ -0,0 +1,32 @@
+#![cfg(feature = "crawl")]
+extern crate glass_fi;
+
+mod support;
+
+use glass_fi::crawl::HtmlHints;
+use glass_fi::{HttpResponseError, Method, SimpleClient};
+
+// Announces far more body than is sent, so reading past the head fails.
+const TRUNCATED: &[u8] = b"HTTP/1.1 200 OK\r\n\
+    Content-Type: text/html; charset=latin1\r\n\
+    Content-Length: 100000\r\n\r\n\
+    <head><link rel=canonical href=/caf\xe9></head><body>";
+
+fn scan(limit: usize) -> Result<HtmlHints, HttpResponseError> {
+    let response = SimpleClient::new()
+        .request(Method::GET, support::serve_once(TRUNCATED))
+        .send_streaming()
+        .unwrap();
+    HtmlHints::from_streaming_response(response, limit)
+}
+
+#[test]
+fn streaming_scan_reads_only_up_to_the_limit() {
+    let hints = scan(44).unwrap();
+    assert_eq!(Some("/caf\u{e9}".to_string()), hints.canonical);
+    assert_eq!(None, scan(36).unwrap().canonical);
+    match scan(4096) {
+        Err(HttpResponseError::Io(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -0,0 +1,33 @@
+extern crate glass_fi;
+
+use glass_fi::{HttpResponseError, SimpleClient, TransferSizes};
+
+#[test]
+fn data_url_is_answered_locally() {
+    let client = SimpleClient::new();
+    let response = client
+        .get("data:text/plain;base64,SGVsbG8gV29ybGQh")
+        .unwrap();
+    assert_eq!("Hello World!", response.body.text);
+    assert_eq!(TransferSizes::default(), response.transfer_sizes());
+
+    let mut headers = response.head;
+    let content_type = headers.find(|x| x.name == "Content-Type").unwrap().content;
+    assert_eq!("text/plain", content_type);
+}
+
+#[test]
+fn malformed_data_url() {
+    match SimpleClient::new().get("data:no-comma") {
+        Err(HttpResponseError::InvalidDataUrl(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn other_schemes_are_rejected() {
+    match SimpleClient::new().get("ftp://example.com/file") {
+        Err(HttpResponseError::NotHttpScheme) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -0,0 +1,223 @@
+extern crate glass_fi;
+
+mod support;
+
+use glass_fi::{BrowserProfile, Forwarded, HeaderMap, HttpResponseError, Method, SimpleClient};
+
+#[test]
+fn response_headers_are_kept() {
+    let url = support::serve_once(
+        b"HTTP/1.1 200 OK\r\n\
+          ETag: \"v1\"\r\n\
+          Location: /elsewhere\r\n\
+          Set-Cookie: a=1\r\n\
+          set-cookie: b=2\r\n\
+          Content-Length: 0\r\n\r\n",
+    );
+    let response = SimpleClient::new().get(url).unwrap();
+    let headers = &response.headers;
+    assert_eq!(Some("\"v1\""), headers.get("etag"));
+    assert_eq!(Some("/elsewhere"), headers.get("LOCATION"));
+    assert_eq!(Some("0"), headers.get("content-length"));
+    assert_eq!(
+        vec!["a=1", "b=2"],
+        headers.get_all("Set-Cookie").collect::<Vec<_>>()
+    );
+    let names: Vec<&str> = headers.iter().map(|(name, _)| name).collect();
+    assert_eq!(
+        vec![
+            "ETag",
+            "Location",
+            "Set-Cookie",
+            "set-cookie",
+            "Content-Length"
+        ],
+        names
+    );
+}
+
+#[test]
+fn default_and_request_headers_are_sent() {
+    let (url, request) = support::serve_capturing(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let client = SimpleClient::builder()
+        .default_header("User-Agent", "glass-fi-test")
+        .default_header("Accept", "text/html")
+        .build()
+        .unwrap();
+    client
+        .request(Method::GET, url.as_str())
+        .header("accept", "application/json")
+        .header("X-Trace", "1")
+        .header("X-Trace", "2")
+        .send()
+        .unwrap();
+
+    let request = String::from_utf8(request.recv().unwrap()).unwrap();
+    let port = url.rsplit(':').next().unwrap().trim_end_matches('/');
+    assert!(
+        request.contains(&format!("\r\nHost: 127.0.0.1:{}\r\n", port)),
+        "{}",
+        request
+    );
+    assert!(request.contains("\r\nConnection: keep-alive\r\n"));
+    assert!(request.contains("\r\nUser-Agent: glass-fi-test\r\n"));
+    assert!(request.contains("\r\naccept: application/json\r\n"));
+    assert!(!request.contains("text/html"));
+    assert!(request.contains("\r\nX-Trace: 1\r\nX-Trace: 2\r\n"));
+}
+
+#[test]
+fn host_header_can_be_overridden() {
+    let (url, request) = support::serve_capturing(b"HTTP/1.1 204 No Content\r\n\r\n");
+    SimpleClient::new()
+        .request(Method::GET, url)
+        .header("Host", "example.com")
+        .send()
+        .unwrap();
+
+    let request = String::from_utf8(request.recv().unwrap()).unwrap();
+    assert!(request.contains("\r\nHost: example.com\r\n"), "{}", request);
+    assert_eq!(1, request.matches("Host:").count());
+}
+
+#[test]
+fn preserved_header_order_keeps_content_length_in_place() {
+    let (url, request) = support::serve_capturing(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let client = SimpleClient::builder()
+        .preserve_header_order(true)
+        .build()
+        .unwrap();
+    client
+        .request(Method::POST, url)
+        .header("HOST", "example.com")
+        .header("content-length", "0")
+        .header("x-after", "1")
+        .body("hello")
+        .send()
+        .unwrap();
+
+    let request = String::from_utf8(request.recv().unwrap()).unwrap();
+    assert!(
+        request.ends_with(
+            " HTTP/1.1\r\nHOST: example.com\r\ncontent-length: 5\r\nx-after: 1\r\n\
+             Connection: keep-alive\r\n\r\nhello"
+        ),
+        "{}",
+        request
+    );
+}
+
+#[test]
+fn browser_profile_shapes_requests() {
+    let (url, request) = support::serve_capturing(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let client = SimpleClient::builder()
+        .profile(BrowserProfile::Chrome)
+        .default_header("X-Client", "1")
+        .build()
+        .unwrap();
+    client
+        .request(Method::GET, url)
+        .header("Accept-Language", "ja")
+        .send()
+        .unwrap();
+
+    let request = String::from_utf8(request.recv().unwrap()).unwrap();
+    let names: Vec<&str> = request
+        .lines()
+        .skip(1)
+        .take_while(|line| !line.is_empty())
+        .map(|line| line.split(':').next().unwrap())
+        .collect();
+    assert_eq!(
+        vec![
+            "Host",
+            "Connection",
+            "sec-ch-ua",
+            "sec-ch-ua-mobile",
+            "sec-ch-ua-platform",
+            "Upgrade-Insecure-Requests",
+            "User-Agent",
+            "Accept",
+            "Accept-Language",
+            "X-Client"
+        ],
+        names
+    );
+    assert!(
+        request.contains("\r\nAccept-Language: ja\r\n"),
+        "{}",
+        request
+    );
+    assert!(request.contains(" Chrome/"), "{}", request);
+}
+
+#[test]
+fn accept_language_and_content_language() {
+    let (url, request) = support::serve_capturing(
+        b"HTTP/1.1 200 OK\r\nContent-Language: ja-JP, en\r\nContent-Language: de\r\nContent-Length: 0\r\n\r\n",
+    );
+    let client = SimpleClient::builder()
+        .accept_language(&["en"])
+        .build()
+        .unwrap();
+    let response = client
+        .request(Method::GET, url)
+        .accept_language(&["ja-JP", "ja", "en"])
+        .send()
+        .unwrap();
+    assert_eq!(vec!["ja-JP", "en", "de"], response.content_language());
+
+    let request = String::from_utf8(request.recv().unwrap()).unwrap();
+    assert!(
+        request.contains("\r\nAccept-Language: ja-JP, ja;q=0.9, en;q=0.8\r\n"),
+        "{}",
+        request
+    );
+    assert_eq!(1, request.matches("Accept-Language").count(), "{}", request);
+
+    assert!(SimpleClient::builder()
+        .accept_language(&["en_US"])
+        .build()
+        .is_err());
+}
+
+#[test]
+fn forwarded_hops_are_appended() {
+    let (url, request) = support::serve_capturing(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let hop = Forwarded {
+        for_: Some("[2001:db8::17]:4711".to_string()),
+        proto: Some("https".to_string()),
+        ..Forwarded::default()
+    };
+    SimpleClient::new()
+        .request(Method::GET, url)
+        .header("Forwarded", "for=192.0.2.60")
+        .forwarded(&hop)
+        .send()
+        .unwrap();
+
+    let request = String::from_utf8(request.recv().unwrap()).unwrap();
+    let value = request
+        .lines()
+        .find(|line| line.starts_with("Forwarded: "))
+        .unwrap()
+        .trim_start_matches("Forwarded: ");
+    assert_eq!(
+        r#"for=192.0.2.60, for="[2001:db8::17]:4711";proto=https"#,
+        value
+    );
+    let mut headers = HeaderMap::new();
+    headers.append("Forwarded", value);
+    let hops = Forwarded::from_headers(&headers).unwrap();
+    assert_eq!(2, hops.len());
+    assert_eq!(hop, hops[1]);
+
+    match SimpleClient::new()
+        .request(Method::GET, "http://127.0.0.1:1/")
+        .forwarded(&Forwarded::default())
+        .send()
+    {
+        Err(HttpResponseError::InvalidHeader(_)) => {}
+        other => panic!("unexpected result: {:?}", other.map(|r| r.status)),
+    }
+}
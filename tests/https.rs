@@ -0,0 +1,390 @@
+#![cfg(any(feature = "https", feature = "native-tls"))]
+extern crate glass_fi;
+extern crate rustls;
+
+use std::io::{BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::thread;
+
+use glass_fi::{Certificate, HttpResponseError, Identity, SimpleClient, TlsVersion};
+use rustls::internal::pemfile::{certs, pkcs8_private_keys};
+use rustls::{
+    AllowAnyAuthenticatedClient, NoClientAuth, ProtocolVersion, RootCertStore, ServerConfig,
+    ServerSession, Session, StreamOwned,
+};
+
+// Certificates in tests/tls: a test CA, and the certificates it issued for
+// `localhost` and for a client.
+const CA_CERT: &[u8] = include_bytes!("tls/ca.pem");
+const CA_CERT_DER: &[u8] = include_bytes!("tls/ca.der");
+const LOCALHOST_CERT: &[u8] = include_bytes!("tls/localhost.pem");
+const LOCALHOST_KEY: &[u8] = include_bytes!("tls/localhost-key.pem");
+const CLIENT_CERT: &[u8] = include_bytes!("tls/client.pem");
+const CLIENT_KEY: &[u8] = include_bytes!("tls/client-key.pem");
+const OTHER_CERT: &[u8] = include_bytes!("tls/other.pem");
+const OTHER_KEY: &[u8] = include_bytes!("tls/other-key.pem");
+#[cfg(feature = "native-tls")]
+const CLIENT_PKCS12: &[u8] = include_bytes!("tls/client.p12");
+
+/// Serves `response` over TLS to the first connection, as `localhost`, and
+/// returns the port.
+fn serve_tls(response: &'static [u8]) -> u16 {
+    serve_tls_with(
+        ServerConfig::new(NoClientAuth::new()),
+        LOCALHOST_CERT,
+        LOCALHOST_KEY,
+        response,
+    )
+}
+
+/// Like `serve_tls`, but as `other.test`, whatever name the client asked for.
+fn serve_tls_as_other(response: &'static [u8]) -> u16 {
+    serve_tls_with(
+        ServerConfig::new(NoClientAuth::new()),
+        OTHER_CERT,
+        OTHER_KEY,
+        response,
+    )
+}
+
+/// Like `serve_tls`, but requires a client certificate issued by the test CA.
+fn serve_mutual_tls(response: &'static [u8]) -> u16 {
+    let mut roots = RootCertStore::empty();
+    roots
+        .add(&certs(&mut BufReader::new(CA_CERT)).unwrap()[0])
+        .unwrap();
+    serve_tls_with(
+        ServerConfig::new(AllowAnyAuthenticatedClient::new(roots)),
+        LOCALHOST_CERT,
+        LOCALHOST_KEY,
+        response,
+    )
+}
+
+fn serve_tls_with(
+    mut config: ServerConfig,
+    cert: &[u8],
+    key: &[u8],
+    response: &'static [u8],
+) -> u16 {
+    let chain = certs(&mut BufReader::new(cert)).unwrap();
+    let key = pkcs8_private_keys(&mut BufReader::new(key))
+        .unwrap()
+        .remove(0);
+    config.set_single_cert(chain, key).unwrap();
+    let config = Arc::new(config);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        let (socket, _) = listener.accept().unwrap();
+        let mut stream = StreamOwned::new(ServerSession::new(&config), socket);
+        let mut request = Vec::new();
+        let mut buffer = [0; 1024];
+        while !request.ends_with(b"\r\n\r\n") {
+            match stream.read(&mut buffer) {
+                Ok(0) | Err(_) => return,
+                Ok(n) => request.extend_from_slice(&buffer[..n]),
+            }
+        }
+        let _ = stream.write_all(response);
+        stream.sess.send_close_notify();
+        let _ = stream.flush();
+    });
+    port
+}
+
+#[test]
+fn untrusted_certificate_is_rejected() {
+    let port = serve_tls(b"HTTP/1.1 204 No Content\r\n\r\n");
+    match SimpleClient::new().get(format!("https://localhost:{}/", port)) {
+        Err(HttpResponseError::Tls(_)) => {}
+        other => panic!("unexpected result: {:?}", other.map(|r| r.status)),
+    }
+}
+
+#[cfg(feature = "https")]
+#[test]
+fn ip_addresses_cannot_be_verified() {
+    let port = serve_tls(b"HTTP/1.1 204 No Content\r\n\r\n");
+    match SimpleClient::new().get(format!("https://127.0.0.1:{}/", port)) {
+        Err(HttpResponseError::Tls(_)) => {}
+        other => panic!("unexpected result: {:?}", other.map(|r| r.status)),
+    }
+}
+
+#[test]
+fn added_root_certificate_is_trusted() {
+    for certificate in [
+        Certificate::from_pem(CA_CERT).unwrap(),
+        Certificate::from_der(CA_CERT_DER).unwrap(),
+    ] {
+        let port = serve_tls(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello");
+        let client = SimpleClient::builder()
+            .add_root_certificate(certificate)
+            .build()
+            .unwrap();
+        let response = client.get(format!("https://localhost:{}/", port)).unwrap();
+        assert_eq!(200, response.status.as_u16());
+        assert_eq!("hello", response.text());
+    }
+}
+
+#[test]
+fn root_certificates_from_bundle() {
+    let bundle = [LOCALHOST_CERT, CA_CERT].concat();
+    let mut builder = SimpleClient::builder();
+    for certificate in Certificate::from_pem_bundle(&bundle).unwrap() {
+        builder = builder.add_root_certificate(certificate);
+    }
+    let client = builder.build().unwrap();
+    let port = serve_tls(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let response = client.get(format!("https://localhost:{}/", port)).unwrap();
+    assert_eq!(204, response.status.as_u16());
+}
+
+#[test]
+fn unusable_root_certificate_fails_build() {
+    let garbage = Certificate::from_der(&[0x30, 0x03, 0x02, 0x01, 0x00]).unwrap();
+    match SimpleClient::builder()
+        .add_root_certificate(garbage)
+        .build()
+    {
+        Err(HttpResponseError::InvalidCertificate(_)) => {}
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    }
+}
+
+fn trusting_client() -> glass_fi::ClientBuilder {
+    SimpleClient::builder().add_root_certificate(Certificate::from_pem(CA_CERT).unwrap())
+}
+
+#[test]
+fn identity_is_presented_for_mutual_tls() {
+    let port = serve_mutual_tls(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let client = trusting_client().build().unwrap();
+    match client.get(format!("https://localhost:{}/", port)) {
+        Err(HttpResponseError::Tls(_)) | Err(HttpResponseError::Io(_)) => {}
+        other => panic!("unexpected result: {:?}", other.map(|r| r.status)),
+    }
+
+    let identity = Identity::from_pem(&[CLIENT_KEY, CLIENT_CERT].concat()).unwrap();
+    let client = trusting_client().identity(identity).build().unwrap();
+    let port = serve_mutual_tls(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let response = client.get(format!("https://localhost:{}/", port)).unwrap();
+    assert_eq!(204, response.status.as_u16());
+}
+
+#[cfg(feature = "native-tls")]
+#[test]
+fn pkcs12_identity() {
+    let identity = Identity::from_pkcs12_der(CLIENT_PKCS12, "glass-fi");
+    let client = trusting_client().identity(identity).build().unwrap();
+    let port = serve_mutual_tls(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let response = client.get(format!("https://localhost:{}/", port)).unwrap();
+    assert_eq!(204, response.status.as_u16());
+
+    let identity = Identity::from_pkcs12_der(CLIENT_PKCS12, "wrong");
+    match trusting_client().identity(identity).build() {
+        Err(HttpResponseError::InvalidCertificate(_)) => {}
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    }
+}
+
+// SHA-256 hashes of the public keys of tests/tls/localhost.pem and ca.pem.
+const LOCALHOST_PIN: &str = "sha256/9DcGSW85AdAjc6FTOF8fPUdhOid3a9kyrrmIHwYv/uo=";
+const CA_PIN: &str = "sha256//AuKGt6VADI0vbbm/M96nJnK5w9uy3VnuU49G8g+tIk=";
+
+#[test]
+fn pinned_public_key_is_enforced() {
+    let client = trusting_client()
+        .pin_spki_sha256("localhost", CA_PIN)
+        .pin_spki_sha256("localhost", LOCALHOST_PIN)
+        .build()
+        .unwrap();
+    let port = serve_tls(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let response = client.get(format!("https://localhost:{}/", port)).unwrap();
+    assert_eq!(204, response.status.as_u16());
+
+    // The CA's key is in the chain, but only the leaf's counts.
+    let client = trusting_client()
+        .pin_spki_sha256("LOCALHOST", CA_PIN)
+        .build()
+        .unwrap();
+    let port = serve_tls(b"HTTP/1.1 204 No Content\r\n\r\n");
+    match client.get(format!("https://localhost:{}/", port)) {
+        Err(HttpResponseError::CertificatePinMismatch(ref host)) if host == "localhost" => {}
+        other => panic!("unexpected result: {:?}", other.map(|r| r.status)),
+    }
+
+    match SimpleClient::builder()
+        .pin_spki_sha256("localhost", "sha256/short")
+        .build()
+    {
+        Err(HttpResponseError::InvalidCertificate(_)) => {}
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn invalid_certificates_can_be_accepted() {
+    let client = SimpleClient::builder()
+        .danger_accept_invalid_certs(true)
+        .build()
+        .unwrap();
+    let port = serve_tls(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let response = client.get(format!("https://localhost:{}/", port)).unwrap();
+    assert_eq!(204, response.status.as_u16());
+
+    // IP addresses are accepted too, though no certificate names them.
+    let port = serve_tls(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let response = client.get(format!("https://127.0.0.1:{}/", port)).unwrap();
+    assert_eq!(204, response.status.as_u16());
+
+    // Pins are still checked.
+    let client = SimpleClient::builder()
+        .danger_accept_invalid_certs(true)
+        .pin_spki_sha256("localhost", CA_PIN)
+        .build()
+        .unwrap();
+    let port = serve_tls(b"HTTP/1.1 204 No Content\r\n\r\n");
+    match client.get(format!("https://localhost:{}/", port)) {
+        Err(HttpResponseError::CertificatePinMismatch(_)) => {}
+        other => panic!("unexpected result: {:?}", other.map(|r| r.status)),
+    }
+}
+
+#[test]
+fn invalid_hostnames_can_be_accepted() {
+    let port = serve_tls_as_other(b"HTTP/1.1 204 No Content\r\n\r\n");
+    match trusting_client()
+        .build()
+        .unwrap()
+        .get(format!("https://localhost:{}/", port))
+    {
+        Err(HttpResponseError::Tls(_)) => {}
+        other => panic!("unexpected result: {:?}", other.map(|r| r.status)),
+    }
+
+    let client = trusting_client()
+        .danger_accept_invalid_hostnames(true)
+        .build()
+        .unwrap();
+    let port = serve_tls_as_other(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let response = client.get(format!("https://localhost:{}/", port)).unwrap();
+    assert_eq!(204, response.status.as_u16());
+
+    // The chain is still verified.
+    let client = SimpleClient::builder()
+        .danger_accept_invalid_hostnames(true)
+        .build()
+        .unwrap();
+    let port = serve_tls_as_other(b"HTTP/1.1 204 No Content\r\n\r\n");
+    match client.get(format!("https://localhost:{}/", port)) {
+        Err(HttpResponseError::Tls(_)) => {}
+        other => panic!("unexpected result: {:?}", other.map(|r| r.status)),
+    }
+}
+
+#[test]
+fn tls_version_bounds() {
+    let client = trusting_client()
+        .max_tls_version(TlsVersion::Tls1_2)
+        .build()
+        .unwrap();
+    let port = serve_tls(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let response = client.get(format!("https://localhost:{}/", port)).unwrap();
+    assert_eq!(204, response.status.as_u16());
+
+    let mut config = ServerConfig::new(NoClientAuth::new());
+    config.versions = vec![ProtocolVersion::TLSv1_2];
+    let port = serve_tls_with(
+        config,
+        LOCALHOST_CERT,
+        LOCALHOST_KEY,
+        b"HTTP/1.1 204 No Content\r\n\r\n",
+    );
+    let client = trusting_client()
+        .min_tls_version(TlsVersion::Tls1_3)
+        .build()
+        .unwrap();
+    match client.get(format!("https://localhost:{}/", port)) {
+        Err(HttpResponseError::Tls(_)) | Err(HttpResponseError::Io(_)) => {}
+        other => panic!("unexpected result: {:?}", other.map(|r| r.status)),
+    }
+
+    match SimpleClient::builder()
+        .min_tls_version(TlsVersion::Tls1_3)
+        .max_tls_version(TlsVersion::Tls1_2)
+        .build()
+    {
+        Err(HttpResponseError::UnsupportedTlsVersion(_)) => {}
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    }
+}
+
+#[cfg(feature = "https")]
+#[test]
+fn rustls_cannot_negotiate_legacy_versions() {
+    match SimpleClient::builder()
+        .max_tls_version(TlsVersion::Tls1_1)
+        .build()
+    {
+        Err(HttpResponseError::UnsupportedTlsVersion(_)) => {}
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    }
+    assert!(SimpleClient::builder()
+        .min_tls_version(TlsVersion::Tls1_0)
+        .build()
+        .is_ok());
+}
+
+#[test]
+fn negotiated_parameters_are_reported() {
+    let client = trusting_client()
+        .max_tls_version(TlsVersion::Tls1_2)
+        .build()
+        .unwrap();
+    let port = serve_tls(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let response = client.get(format!("https://localhost:{}/", port)).unwrap();
+    let info = response.tls_info().unwrap();
+    assert_eq!(
+        vec![Certificate::from_pem(LOCALHOST_CERT).unwrap()],
+        info.peer_certificates()
+    );
+    // The server does not take part in ALPN.
+    assert_eq!(None, info.alpn_protocol());
+    if cfg!(feature = "https") {
+        assert_eq!(Some(TlsVersion::Tls1_2), info.version());
+        assert_eq!(
+            Some("TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256"),
+            info.cipher_suite()
+        );
+    } else {
+        assert_eq!(None, info.version());
+    }
+
+    let mut config = ServerConfig::new(NoClientAuth::new());
+    config.set_protocols(&[b"h2".to_vec(), b"http/1.1".to_vec()]);
+    let port = serve_tls_with(
+        config,
+        LOCALHOST_CERT,
+        LOCALHOST_KEY,
+        b"HTTP/1.1 204 No Content\r\n\r\n",
+    );
+    let response = trusting_client()
+        .build()
+        .unwrap()
+        .get(format!("https://localhost:{}/", port))
+        .unwrap();
+    let info = response.tls_info().unwrap();
+    assert_eq!(Some(&b"http/1.1"[..]), info.alpn_protocol());
+    if cfg!(feature = "https") {
+        assert_eq!(Some(TlsVersion::Tls1_3), info.version());
+        assert_eq!(Some("TLS_CHACHA20_POLY1305_SHA256"), info.cipher_suite());
+    }
+
+    let response = SimpleClient::new().get("data:,plain").unwrap();
+    assert!(response.tls_info().is_none());
+}
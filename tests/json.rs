@@ -0,0 +1,63 @@
+#![cfg(feature = "json")]
+extern crate glass_fi;
+extern crate serde_json;
+
+mod support;
+
+use std::collections::BTreeMap;
+
+use glass_fi::{HttpResponseError, Method, SimpleClient};
+use serde_json::Value;
+
+#[test]
+fn json_body_is_sent() {
+    let (url, request) = support::serve_capturing(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let mut value = BTreeMap::new();
+    value.insert("name", "glass-fi");
+    value.insert("lang", "rust");
+    SimpleClient::new()
+        .request(Method::POST, url)
+        .header("Content-Type", "text/plain")
+        .json(&value)
+        .send()
+        .unwrap();
+
+    let request = String::from_utf8(request.recv().unwrap()).unwrap();
+    assert!(request.contains("\r\nContent-Type: application/json\r\n"));
+    assert!(!request.contains("text/plain"));
+    assert!(request.ends_with("\r\n\r\n{\"lang\":\"rust\",\"name\":\"glass-fi\"}"));
+}
+
+#[test]
+fn json_response_is_parsed() {
+    let url = support::serve_once(
+        b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 25\r\n\r\n\
+          {\"id\":7,\"tags\":[\"a\",\"b\"]}",
+    );
+    let response = SimpleClient::new().get(url).unwrap();
+    let value: Value = response.json().unwrap();
+    assert_eq!(7, value["id"]);
+    assert_eq!("b", value["tags"][1]);
+    let pairs: BTreeMap<String, Value> = response.json().unwrap();
+    assert_eq!(2, pairs.len());
+
+    match response.json::<Vec<u8>>() {
+        Err(HttpResponseError::Json(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn json_serialization_errors_surface_on_send() {
+    let mut value = BTreeMap::new();
+    value.insert(vec![1], "keys must be strings");
+    let err = SimpleClient::new()
+        .request(Method::POST, "http://127.0.0.1:1/")
+        .json(&value)
+        .send()
+        .unwrap_err();
+    match err {
+        HttpResponseError::Json(_) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "loadgen")]
+extern crate glass_fi;
+
+mod support;
+
+use std::time::Duration;
+
+use glass_fi::loadgen::{LoadGenerator, Schedule};
+use glass_fi::{Method, SimpleClient};
+
+#[test]
+fn closed_loop_collects_latencies() {
+    let url = support::serve_forever(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok");
+    let report = LoadGenerator::new(SimpleClient::new(), |client: &SimpleClient| {
+        client.request(Method::GET, url.as_str())
+    })
+    .schedule(Schedule::ClosedLoop { concurrency: 4 })
+    .duration(Duration::from_millis(200))
+    .run();
+
+    assert!(report.succeeded > 0);
+    assert_eq!(0, report.failed);
+    assert_eq!(report.succeeded, report.latency.len());
+    assert!(report.latency.value_at_quantile(0.5) <= report.latency.max());
+}
+
+#[test]
+fn open_loop_keeps_the_rate() {
+    let url =
+        support::serve_forever(b"HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\n\r\n");
+    let report = LoadGenerator::new(SimpleClient::new(), |client: &SimpleClient| {
+        client.request(Method::GET, url.as_str())
+    })
+    .schedule(Schedule::OpenLoop { rate: 100.0 })
+    .duration(Duration::from_millis(300))
+    .max_in_flight(8)
+    .run();
+
+    assert_eq!(30, report.sent());
+    assert_eq!(30, report.error_statuses);
+    assert!(report.elapsed >= Duration::from_millis(290));
+}
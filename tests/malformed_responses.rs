@@ -0,0 +1,66 @@
+extern crate glass_fi;
+
+mod support;
+
+use glass_fi::{HttpResponseError, SimpleClient};
+
+#[test]
+fn well_formed_response() {
+    let url = support::serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello");
+    let response = SimpleClient::new().get(url).unwrap();
+    assert_eq!("hello", response.body.text);
+}
+
+#[test]
+fn header_without_colon() {
+    let url = support::serve_once(b"HTTP/1.1 200 OK\r\nno colon here\r\n\r\n");
+    match SimpleClient::new().get(url) {
+        Err(HttpResponseError::InvalidHeader(ref line)) => assert_eq!("no colon here", line),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn non_numeric_content_length() {
+    let url = support::serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: five\r\n\r\nhello");
+    match SimpleClient::new().get(url) {
+        Err(HttpResponseError::InvalidContentLength(ref value)) => assert_eq!("five", value),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn empty_server_output() {
+    let url = support::serve_once(b"");
+    match SimpleClient::new().get(url) {
+        Err(ref err @ HttpResponseError::Io(_)) => assert!(err.is_retryable()),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn early_close_in_head() {
+    let url = support::serve_once(b"HTTP/1.1 200 OK\r\nContent-Le");
+    match SimpleClient::new().head(url) {
+        Err(HttpResponseError::Io(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn early_close_in_body() {
+    let url = support::serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\nshort");
+    match SimpleClient::new().get(url) {
+        Err(HttpResponseError::Io(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn missing_status_line() {
+    let url = support::serve_once(b"hello\r\n\r\n");
+    match SimpleClient::new().get(url) {
+        Err(HttpResponseError::InvalidStatusLine(ref line)) => assert_eq!("hello", line),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -0,0 +1,69 @@
+extern crate glass_fi;
+
+mod support;
+
+use glass_fi::{Method, SimpleClient};
+
+#[test]
+fn post_sends_body_with_content_length() {
+    let (url, request) =
+        support::serve_capturing(b"HTTP/1.1 201 Created\r\nContent-Length: 2\r\n\r\nok");
+    let response = SimpleClient::new()
+        .post(url, "{\"name\":\"glass-fi\"}")
+        .unwrap();
+    assert_eq!("ok", response.body.text);
+    assert_eq!(19, response.transfer_sizes().body_bytes_sent);
+
+    let request = String::from_utf8(request.recv().unwrap()).unwrap();
+    assert!(request.starts_with("POST "));
+    assert!(request.contains("Content-Length: 19\r\n"));
+    assert!(request.ends_with("\r\n\r\n{\"name\":\"glass-fi\"}"));
+}
+
+#[test]
+fn post_accepts_binary_body() {
+    let (url, request) = support::serve_capturing(b"HTTP/1.1 204 No Content\r\n\r\n");
+    SimpleClient::new()
+        .post(url, vec![0u8, 159, 146, 150])
+        .unwrap();
+    assert!(request.recv().unwrap().ends_with(&[0u8, 159, 146, 150]));
+}
+
+#[test]
+fn methods_on_the_request_line() {
+    let client = SimpleClient::new();
+    let response = b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n";
+
+    let (url, request) = support::serve_capturing(response);
+    client.put(url, "a").unwrap();
+    assert!(request.recv().unwrap().starts_with(b"PUT "));
+
+    let (url, request) = support::serve_capturing(response);
+    client.patch(url, "a").unwrap();
+    assert!(request.recv().unwrap().starts_with(b"PATCH "));
+
+    let (url, request) = support::serve_capturing(response);
+    client.delete(url).unwrap();
+    assert!(request.recv().unwrap().starts_with(b"DELETE "));
+
+    let (url, request) = support::serve_capturing(response);
+    client.options(url).unwrap();
+    assert!(request.recv().unwrap().starts_with(b"OPTIONS "));
+
+    let (url, request) = support::serve_capturing(response);
+    client
+        .request("PROPFIND".parse::<Method>().unwrap(), url)
+        .send()
+        .unwrap();
+    assert!(request.recv().unwrap().starts_with(b"PROPFIND "));
+}
+
+#[test]
+fn head_ignores_content_length() {
+    let url = support::serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 1000\r\n\r\n");
+    let response = SimpleClient::new()
+        .request(Method::HEAD, url)
+        .send()
+        .unwrap();
+    assert_eq!("", response.body.text);
+}
@@ -0,0 +1,104 @@
+extern crate glass_fi;
+
+mod support;
+
+use glass_fi::{HttpResponseError, ParseMode, ProtocolWarning, SimpleClient};
+
+fn strict() -> SimpleClient {
+    SimpleClient::builder()
+        .parse_mode(ParseMode::Strict)
+        .build()
+        .unwrap()
+}
+
+fn assert_violation(response: &'static [u8]) {
+    match strict().get(support::serve_once(response)) {
+        Err(HttpResponseError::ProtocolViolation(_)) => {}
+        other => panic!("unexpected result: {:?}", other.map(|r| r.status)),
+    }
+}
+
+const DEVIATIONS: [&[u8]; 5] = [
+    b"HTTP/1.1 200 OK\nContent-Length: 2\n\nok",
+    b"HTTP/1.1 200 OK\r\nContent-Length: 2\n\r\nok",
+    b"HTTP/1.1 200 OK\r\nContent-Length : 2\r\n\r\nok",
+    b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nContent-Length: 2\r\n\r\nok",
+    b"HTTP/1.1 200 OK\r\nContent-Length: 9\r\nTransfer-Encoding: chunked\r\n\r\n2\r\nok\r\n0\r\n\r\n",
+];
+
+#[test]
+fn lenient_mode_tolerates_deviations() {
+    for response in DEVIATIONS.iter() {
+        let response = SimpleClient::new()
+            .get(support::serve_once(response))
+            .unwrap();
+        assert_eq!("ok", response.text());
+    }
+}
+
+#[test]
+fn strict_mode_rejects_deviations() {
+    for response in DEVIATIONS.iter() {
+        assert_violation(response);
+    }
+    let url = support::serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok");
+    assert_eq!("ok", strict().get(url).unwrap().text());
+}
+
+#[test]
+fn conflicting_lengths_are_always_rejected() {
+    let url = support::serve_once(
+        b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nContent-Length: 3\r\n\r\nok!",
+    );
+    match SimpleClient::new().get(url) {
+        Err(HttpResponseError::InvalidContentLength(_)) => {}
+        other => panic!("unexpected result: {:?}", other.map(|r| r.status)),
+    }
+}
+
+#[test]
+fn lenient_mode_reports_ambiguous_framing() {
+    let url =
+        support::serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nContent-Length: 2\r\n\r\nok");
+    let response = SimpleClient::new().get(url).unwrap();
+    assert_eq!(
+        &[ProtocolWarning::DuplicateContentLength],
+        response.warnings()
+    );
+
+    let url = support::serve_once(DEVIATIONS[4]);
+    let response = SimpleClient::new().get(url).unwrap();
+    assert_eq!("ok", response.text());
+    assert_eq!(
+        &[ProtocolWarning::ContentLengthWithTransferEncoding],
+        response.warnings()
+    );
+
+    let url = support::serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok");
+    assert!(SimpleClient::new().get(url).unwrap().warnings().is_empty());
+}
+
+#[test]
+fn lenient_mode_reports_bare_line_feeds() {
+    let url = support::serve_once(DEVIATIONS[0]);
+    let response = SimpleClient::new().get(url).unwrap();
+    assert_eq!(&[ProtocolWarning::BareLineFeed], response.warnings());
+
+    let url = support::serve_once(
+        b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n2\nok\r\n0\r\n\r\n",
+    );
+    let response = SimpleClient::new().get(url).unwrap();
+    assert_eq!("ok", response.text());
+    assert_eq!(&[ProtocolWarning::BareLineFeed], response.warnings());
+}
+
+#[test]
+fn lenient_mode_reports_whitespace_in_header_names() {
+    let url = support::serve_once(DEVIATIONS[2]);
+    let response = SimpleClient::new().get(url).unwrap();
+    assert_eq!("2", response.headers.get("Content-Length").unwrap());
+    assert_eq!(
+        &[ProtocolWarning::WhitespaceInHeaderName],
+        response.warnings()
+    );
+}
@@ -0,0 +1,234 @@
+extern crate glass_fi;
+
+mod support;
+
+use std::time::Duration;
+
+use glass_fi::{
+    HttpResponseError, Method, RedirectAction, RedirectAttempt, RedirectPolicy, SimpleClient,
+};
+
+fn following(limit: usize) -> SimpleClient {
+    SimpleClient::builder()
+        .redirect(RedirectPolicy::Limited(limit))
+        .build()
+        .unwrap()
+}
+
+fn redirect(status: &str, location: &str) -> Vec<u8> {
+    format!(
+        "HTTP/1.1 {}\r\nLocation: {}\r\nContent-Length: 0\r\n\r\n",
+        status, location
+    )
+    .into_bytes()
+}
+
+fn ok(body: &str) -> Vec<u8> {
+    format!(
+        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+        body.len(),
+        body
+    )
+    .into_bytes()
+}
+
+fn received(requests: &::std::sync::mpsc::Receiver<Vec<u8>>) -> String {
+    String::from_utf8(requests.recv().unwrap()).unwrap()
+}
+
+#[test]
+fn redirects_are_not_followed_by_default() {
+    let (url, _) = support::serve_sequence(vec![redirect("302 Found", "/next")]);
+    let response = SimpleClient::new().get(url).unwrap();
+    assert_eq!(302, response.status.as_u16());
+    assert_eq!(Some("/next"), response.headers.get("Location"));
+}
+
+#[test]
+fn relative_redirects_are_followed() {
+    let (url, requests) = support::serve_sequence(vec![
+        redirect("301 Moved Permanently", "/a/b"),
+        redirect("302 Found", "c?x=1"),
+        ok("done"),
+    ]);
+    let response = following(5).get(url.as_str()).unwrap();
+    assert_eq!("done", response.body.text);
+    assert_eq!(format!("{}a/c?x=1", url), response.url().as_str());
+    assert!(received(&requests).starts_with("GET / "));
+    assert!(received(&requests).starts_with("GET /a/b "));
+    assert!(received(&requests).starts_with("GET /a/c?x=1 "));
+}
+
+#[test]
+fn see_other_turns_post_into_get() {
+    let (url, requests) =
+        support::serve_sequence(vec![redirect("303 See Other", "/result"), ok("")]);
+    following(5)
+        .request(Method::POST, url)
+        .header("Content-Type", "text/plain")
+        .body("payload")
+        .send()
+        .unwrap();
+    assert!(received(&requests).ends_with("\r\n\r\npayload"));
+    let second = received(&requests);
+    assert!(second.starts_with("GET /result "), "{}", second);
+    assert!(!second.contains("Content-Type"));
+    assert!(!second.contains("Content-Length"));
+    assert!(second.ends_with("\r\n\r\n"));
+}
+
+#[test]
+fn temporary_redirect_repeats_the_request() {
+    let (url, requests) =
+        support::serve_sequence(vec![redirect("307 Temporary Redirect", "/again"), ok("")]);
+    following(5)
+        .request(Method::PUT, url)
+        .body("payload")
+        .send()
+        .unwrap();
+    received(&requests);
+    let second = received(&requests);
+    assert!(second.starts_with("PUT /again "), "{}", second);
+    assert!(second.ends_with("\r\n\r\npayload"));
+}
+
+#[test]
+fn credentials_are_dropped_across_origins() {
+    let (target, target_requests) = support::serve_sequence(vec![ok("")]);
+    let (url, _) = support::serve_sequence(vec![redirect("302 Found", &target)]);
+    following(5)
+        .request(Method::GET, url)
+        .header("Authorization", "Bearer secret")
+        .header("X-Trace", "1")
+        .send()
+        .unwrap();
+    let second = received(&target_requests);
+    assert!(!second.contains("Authorization"), "{}", second);
+    assert!(second.contains("\r\nX-Trace: 1\r\n"));
+}
+
+#[test]
+fn default_credentials_are_dropped_across_origins() {
+    let (target, target_requests) = support::serve_sequence(vec![ok("")]);
+    let (url, requests) = support::serve_sequence(vec![redirect("302 Found", &target)]);
+    SimpleClient::builder()
+        .redirect(RedirectPolicy::Limited(5))
+        .default_header("Authorization", "Bearer secret")
+        .default_header("Cookie", "session=1")
+        .default_header("X-Trace", "1")
+        .build()
+        .unwrap()
+        .get(url)
+        .unwrap();
+    let first = received(&requests);
+    assert!(
+        first.contains("\r\nAuthorization: Bearer secret\r\n"),
+        "{}",
+        first
+    );
+    let second = received(&target_requests);
+    assert!(!second.contains("Authorization"), "{}", second);
+    assert!(!second.contains("Cookie"), "{}", second);
+    assert!(second.contains("\r\nX-Trace: 1\r\n"));
+}
+
+#[test]
+fn redirects_to_other_schemes_are_returned() {
+    for location in &["data:text/plain,made%20up", "ftp://example.com/file"] {
+        let (url, _) = support::serve_sequence(vec![redirect("302 Found", location)]);
+        let response = following(5).get(url.as_str()).unwrap();
+        assert_eq!(302, response.status.as_u16());
+        assert_eq!(Some(*location), response.headers.get("Location"));
+        assert_eq!(url, response.url().as_str());
+    }
+}
+
+#[test]
+fn redirect_limit_is_enforced() {
+    let (url, _) = support::serve_sequence(vec![
+        redirect("302 Found", "/one"),
+        redirect("302 Found", "/two"),
+    ]);
+    match following(1).get(url.as_str()) {
+        Err(HttpResponseError::TooManyRedirects(next)) => {
+            assert_eq!(format!("{}two", url), next)
+        }
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok(response) => panic!("unexpected response: {:?}", response.status),
+    }
+}
+
+fn hooked<F>(hook: F) -> SimpleClient
+where
+    F: Fn(&mut RedirectAttempt) -> RedirectAction + Send + Sync + 'static,
+{
+    SimpleClient::builder()
+        .redirect(RedirectPolicy::Limited(5))
+        .redirect_hook(hook)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn redirect_hook_can_resign_requests() {
+    let (target, target_requests) = support::serve_sequence(vec![ok("signed")]);
+    let (url, _) = support::serve_sequence(vec![redirect("307 Temporary Redirect", &target)]);
+    let client = hooked(|attempt| {
+        assert_eq!(307, attempt.status().as_u16());
+        assert_eq!(0, attempt.followed());
+        assert!(!attempt.headers().contains_key("Authorization"));
+        let signature = format!("Signed {}", attempt.url().port().unwrap());
+        attempt.headers_mut().insert("Authorization", signature);
+        RedirectAction::Follow
+    });
+    let response = client
+        .request(Method::GET, url)
+        .header("Authorization", "Signed origin")
+        .send()
+        .unwrap();
+    assert_eq!("signed", response.body.text);
+    let second = received(&target_requests);
+    let port = target.trim_end_matches('/').rsplit(':').next().unwrap();
+    assert!(
+        second.contains(&format!("\r\nAuthorization: Signed {}\r\n", port)),
+        "{}",
+        second
+    );
+}
+
+#[test]
+fn redirect_hook_can_stop_or_deny() {
+    let (url, _) = support::serve_sequence(vec![redirect("302 Found", "/next")]);
+    let response = hooked(|_| RedirectAction::Stop).get(url).unwrap();
+    assert_eq!(302, response.status.as_u16());
+
+    let (url, _) = support::serve_sequence(vec![redirect("302 Found", "/next")]);
+    match hooked(|_| RedirectAction::Deny).get(url.as_str()) {
+        Err(HttpResponseError::RedirectDenied(next)) => assert_eq!(format!("{}next", url), next),
+        other => panic!("unexpected result: {:?}", other.map(|r| r.status)),
+    }
+
+    let (url, _) = support::serve_sequence(vec![redirect("302 Found", "/next")]);
+    let client = hooked(|attempt| {
+        attempt.headers_mut().insert("X-Bad", "a\r\nInjected: yes");
+        RedirectAction::Follow
+    });
+    match client.get(url) {
+        Err(HttpResponseError::InvalidHeader(_)) => {}
+        other => panic!("unexpected result: {:?}", other.map(|r| r.status)),
+    }
+}
+
+#[test]
+fn client_defaults_apply_to_requests() {
+    let url = support::serve_silent(Duration::from_secs(5));
+    let client = SimpleClient::builder()
+        .timeout(Duration::from_millis(200))
+        .build()
+        .unwrap();
+    assert!(client.get(url).unwrap_err().is_timeout());
+
+    let url = support::serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 11\r\n\r\nhello world");
+    let client = SimpleClient::builder().read_buffer_size(1).build().unwrap();
+    assert_eq!("hello world", client.get(url).unwrap().body.text);
+}
@@ -0,0 +1,139 @@
+extern crate glass_fi;
+
+mod support;
+
+use std::time::{Duration, Instant};
+
+use glass_fi::{HttpResponseError, Method, Phase, SimpleClient, UrlBuilder, UrlError};
+
+#[test]
+fn headers_and_body_are_sent() {
+    let (url, request) =
+        support::serve_capturing(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok");
+    let response = SimpleClient::new()
+        .request(Method::PUT, url)
+        .header("X-Request-Id", "42")
+        .header("Accept", "text/plain")
+        .body("payload")
+        .send()
+        .unwrap();
+    assert_eq!("ok", response.body.text);
+
+    let request = String::from_utf8(request.recv().unwrap()).unwrap();
+    assert!(request.starts_with("PUT "));
+    assert!(request.contains("\r\nX-Request-Id: 42\r\n"));
+    assert!(request.contains("\r\nAccept: text/plain\r\n"));
+    assert!(request.contains("\r\nContent-Length: 7\r\n"));
+    assert!(request.ends_with("\r\n\r\npayload"));
+}
+
+#[test]
+fn url_builder_can_be_sent() {
+    let (url, request) = support::serve_capturing(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let url = UrlBuilder::parse(&url)
+        .path_segments(&["files", "a b"])
+        .query("q", "x&y")
+        .fragment(Some("top"));
+    let response = SimpleClient::new().get(url).unwrap();
+    assert_eq!(204, response.status.as_u16());
+    let request = String::from_utf8(request.recv().unwrap()).unwrap();
+    assert!(
+        request.starts_with("GET /files/a%20b?q=x%26y HTTP/1.1\r\n"),
+        "{}",
+        request
+    );
+
+    match SimpleClient::new().get(UrlBuilder::new("ftp", "example.com")) {
+        Err(HttpResponseError::InvalidUrl(UrlError::UnsupportedScheme(_))) => {}
+        other => panic!("unexpected result: {:?}", other.map(|r| r.status)),
+    }
+}
+
+#[test]
+fn timeout_fires_when_server_stalls() {
+    let url = support::serve_silent(Duration::from_secs(5));
+    let started = Instant::now();
+    let err = SimpleClient::new()
+        .request(Method::GET, url)
+        .timeout(Duration::from_millis(200))
+        .send()
+        .unwrap_err();
+    assert!(err.is_timeout(), "{:?}", err);
+    assert!(started.elapsed() < Duration::from_secs(4));
+}
+
+#[test]
+fn invalid_url_is_reported_on_send() {
+    assert!(SimpleClient::new()
+        .request(Method::GET, "not a url")
+        .header("X", "y")
+        .send()
+        .is_err());
+}
+
+#[test]
+fn deadline_fires_on_slow_drip() {
+    let url = support::serve_dripping(
+        b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\n",
+        &[b'x'; 100],
+        Duration::from_millis(20),
+    );
+    let started = Instant::now();
+    let err = SimpleClient::new()
+        .request(Method::GET, url)
+        .timeout(Duration::from_millis(500))
+        .deadline(Duration::from_millis(300))
+        .send()
+        .unwrap_err();
+    match err {
+        HttpResponseError::DeadlineExceeded(Phase::ReadBody) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+    assert!(finished_near_deadline(started));
+}
+
+#[test]
+fn deadline_reports_the_phase_reached() {
+    let url = support::serve_silent(Duration::from_secs(5));
+    let err = SimpleClient::new()
+        .request(Method::GET, url)
+        .deadline(Duration::from_millis(200))
+        .send()
+        .unwrap_err();
+    assert!(err.is_timeout());
+    match err {
+        HttpResponseError::DeadlineExceeded(Phase::ReadHead) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn deadline_allows_fast_responses() {
+    let url = support::serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok");
+    let response = SimpleClient::new()
+        .request(Method::GET, url)
+        .deadline(Duration::from_secs(5))
+        .send()
+        .unwrap();
+    assert_eq!("ok", response.body.text);
+}
+
+fn finished_near_deadline(started: Instant) -> bool {
+    let elapsed = started.elapsed();
+    elapsed >= Duration::from_millis(300) && elapsed < Duration::from_secs(2)
+}
+
+#[test]
+fn form_body_is_sent() {
+    let (url, request) = support::serve_capturing(b"HTTP/1.1 204 No Content\r\n\r\n");
+    SimpleClient::new()
+        .request(Method::POST, url)
+        .form(&[("user", "alice"), ("pass", "s3cret!")])
+        .send()
+        .unwrap();
+
+    let request = String::from_utf8(request.recv().unwrap()).unwrap();
+    assert!(request.contains("\r\nContent-Type: application/x-www-form-urlencoded\r\n"));
+    assert!(request.contains("\r\nContent-Length: 25\r\n"));
+    assert!(request.ends_with("\r\n\r\nuser=alice&pass=s3cret%21"));
+}
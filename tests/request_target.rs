@@ -0,0 +1,52 @@
+extern crate glass_fi;
+
+mod support;
+
+use glass_fi::{Method, SimpleClient};
+
+fn request_line(url: &str) -> String {
+    let (base, request) = support::serve_capturing(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let url = format!("{}{}", base.trim_end_matches('/'), url);
+    SimpleClient::new().get(url).unwrap();
+    let request = String::from_utf8(request.recv().unwrap()).unwrap();
+    request.lines().next().unwrap().to_string()
+}
+
+#[test]
+fn path_and_query_are_sent() {
+    assert!(request_line("/").starts_with("GET / "));
+    assert!(request_line("").starts_with("GET / "));
+    assert!(request_line("/a/b.html?x=1&y=2").starts_with("GET /a/b.html?x=1&y=2 "));
+    assert!(request_line("/search?").starts_with("GET /search? "));
+}
+
+#[test]
+fn fragment_is_stripped() {
+    assert!(request_line("/page?q=1#section").starts_with("GET /page?q=1 "));
+    assert!(request_line("/page#top").starts_with("GET /page "));
+}
+
+#[test]
+fn target_is_percent_encoded() {
+    assert!(request_line("/caf%C3%A9/a%20b").starts_with("GET /caf%C3%A9/a%20b "));
+    assert!(request_line("/café/a b").starts_with("GET /caf%C3%A9/a%20b "));
+    assert!(
+        request_line("/q?name=J%C3%BCrgen&x=a%26b").starts_with("GET /q?name=J%C3%BCrgen&x=a%26b ")
+    );
+}
+
+#[test]
+fn query_builder_reaches_the_server() {
+    let (url, request) = support::serve_capturing(b"HTTP/1.1 204 No Content\r\n\r\n");
+    SimpleClient::new()
+        .request(Method::GET, format!("{}items?sort=asc", url))
+        .query(&[("q", "rust lang"), ("tag", "a&b")])
+        .send()
+        .unwrap();
+    let request = String::from_utf8(request.recv().unwrap()).unwrap();
+    assert!(
+        request.starts_with("GET /items?sort=asc&q=rust+lang&tag=a%26b "),
+        "{}",
+        request
+    );
+}
@@ -0,0 +1,74 @@
+extern crate glass_fi;
+
+mod support;
+
+use glass_fi::{HttpResponseError, ParseMode, ProtocolWarning, SimpleClient, StatusClass};
+
+#[test]
+fn status_and_reason_are_exposed() {
+    let url = support::serve_once(b"HTTP/1.1 404 Nothing Here\r\nContent-Length: 4\r\n\r\nnope");
+    let response = SimpleClient::new().get(url).unwrap();
+    assert_eq!(404, response.status.as_u16());
+    assert!(response.status.is_client_error());
+    assert_eq!("Nothing Here", response.reason);
+    assert_eq!("nope", response.body.text);
+
+    match response.error_for_status() {
+        Err(HttpResponseError::Status(status)) => assert_eq!(404, status.as_u16()),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn no_body_statuses_ignore_content_length() {
+    let url = support::serve_once(b"HTTP/1.1 304 Not Modified\r\nContent-Length: 1000\r\n\r\n");
+    let response = SimpleClient::new().get(url).unwrap();
+    assert!(response.status.is_redirection());
+    assert_eq!("", response.body.text);
+}
+
+#[test]
+fn invalid_status_code() {
+    let url = support::serve_once(b"HTTP/1.1 2OO OK\r\n\r\n");
+    match SimpleClient::new().get(url) {
+        Err(HttpResponseError::InvalidStatusLine(ref line)) => assert_eq!("HTTP/1.1 2OO OK", line),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn interim_responses_are_skipped() {
+    let url = support::serve_once(
+        b"HTTP/1.1 102 Processing\r\n\r\n\
+          HTTP/1.1 103 Early Hints\r\nLink: </style.css>; rel=preload\r\n\r\n\
+          HTTP/1.1 226 IM Used\r\nIM: diffe\r\nContent-Length: 5\r\n\r\ndelta",
+    );
+    let response = SimpleClient::new().get(url).unwrap();
+    assert_eq!(226, response.status.as_u16());
+    assert_eq!(Some(StatusClass::Success), response.status.class());
+    assert!(response.status.is_instance_manipulated());
+    assert!(response.headers.get("Link").is_none());
+    assert_eq!("delta", response.body.text);
+}
+
+#[test]
+fn reset_content_ignores_announced_content() {
+    let response = b"HTTP/1.1 205 Reset Content\r\nContent-Length: 4\r\n\r\nbody";
+    let response_of = |mode| {
+        SimpleClient::builder()
+            .parse_mode(mode)
+            .build()
+            .unwrap()
+            .get(support::serve_once(response))
+    };
+    let lenient = response_of(ParseMode::Lenient).unwrap();
+    assert_eq!("", lenient.body.text);
+    assert_eq!(
+        &[ProtocolWarning::ContentWithResetContent][..],
+        lenient.warnings()
+    );
+    match response_of(ParseMode::Strict) {
+        Err(HttpResponseError::ProtocolViolation(_)) => {}
+        other => panic!("unexpected result: {:?}", other.map(|r| r.status)),
+    }
+}
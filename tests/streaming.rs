@@ -0,0 +1,140 @@
+extern crate glass_fi;
+extern crate tokio;
+
+mod support;
+
+use std::time::Duration;
+
+use glass_fi::{HttpResponseError, Method, SimpleClient};
+use tokio::prelude::*;
+
+fn collect(url: String, client: &SimpleClient) -> Vec<Result<Vec<u8>, HttpResponseError>> {
+    client
+        .request(Method::GET, url)
+        .send_streaming()
+        .unwrap()
+        .bytes_stream()
+        .wait()
+        .map(|chunk| chunk.map(|chunk| chunk.to_vec()))
+        .collect()
+}
+
+#[test]
+fn chunked_body_is_streamed_without_framing() {
+    let url = support::serve_once(
+        b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+          5\r\nhello\r\n7;ext=1\r\n, world\r\n0\r\nTrailer: x\r\n\r\n",
+    );
+    let chunks: Vec<_> = collect(url, &SimpleClient::new())
+        .into_iter()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(vec![b"hello".to_vec(), b", world".to_vec()], chunks);
+}
+
+#[test]
+fn pieces_are_bounded_by_the_read_buffer() {
+    let url = support::serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\n0123456789");
+    let client = SimpleClient::builder().read_buffer_size(4).build().unwrap();
+    let chunks: Vec<_> = collect(url, &client)
+        .into_iter()
+        .map(Result::unwrap)
+        .collect();
+    assert!(chunks.iter().all(|chunk| chunk.len() <= 4), "{:?}", chunks);
+    assert_eq!(b"0123456789".to_vec(), chunks.concat());
+}
+
+#[test]
+fn pieces_arrive_before_the_body_is_complete() {
+    let url = support::serve_dripping(
+        b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n",
+        b"abcde",
+        Duration::from_millis(50),
+    );
+    let response = SimpleClient::new()
+        .request(Method::GET, url)
+        .send_streaming()
+        .unwrap();
+    assert_eq!(200, response.status.as_u16());
+    let mut body = response.bytes_stream().wait();
+    let first = body.next().unwrap().unwrap();
+    assert!(first.len() < 5, "{:?}", first);
+    let rest: Vec<u8> = body.flat_map(|chunk| chunk.unwrap().to_vec()).collect();
+    assert_eq!(b"abcde".to_vec(), [&first[..], &rest[..]].concat());
+}
+
+#[test]
+fn truncated_body_ends_with_an_error() {
+    let url = support::serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nshort");
+    let mut chunks = collect(url, &SimpleClient::new());
+    match chunks.pop() {
+        Some(Err(HttpResponseError::Io(_))) => {}
+        other => panic!("unexpected end of stream: {:?}", other),
+    }
+    let received: Vec<u8> = chunks.into_iter().flat_map(Result::unwrap).collect();
+    assert_eq!(b"short".to_vec(), received);
+}
+
+#[test]
+fn head_errors_are_returned_up_front() {
+    let url = support::serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: x\r\n\r\n");
+    match SimpleClient::new()
+        .request(Method::GET, url)
+        .send_streaming()
+    {
+        Err(HttpResponseError::InvalidContentLength(_)) => {}
+        other => panic!("unexpected result: {:?}", other.map(|r| r.status)),
+    }
+    let url = support::serve_once(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n");
+    match SimpleClient::new()
+        .request(Method::GET, url)
+        .send_streaming()
+        .unwrap()
+        .error_for_status()
+    {
+        Err(HttpResponseError::Status(status)) => assert_eq!(404, status.as_u16()),
+        other => panic!("unexpected result: {:?}", other.map(|r| r.status)),
+    }
+}
+
+#[test]
+fn data_urls_stream_in_one_piece() {
+    let chunks = collect("data:,hello".to_string(), &SimpleClient::new());
+    assert_eq!(b"hello".to_vec(), chunks[0].as_ref().unwrap().clone());
+    assert_eq!(1, chunks.len());
+}
+
+#[test]
+fn save_to_renames_a_complete_download() {
+    let dir = std::env::temp_dir().join(format!("glass-fi-save-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("download.bin");
+    std::fs::write(&path, b"old").unwrap();
+
+    let url = support::serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nshort");
+    match SimpleClient::new()
+        .request(Method::GET, url)
+        .send_streaming()
+        .unwrap()
+        .save_to(&path)
+    {
+        Err(HttpResponseError::Io(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(b"old".to_vec(), std::fs::read(&path).unwrap());
+
+    let url = support::serve_once(
+        b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+          3\r\nnew\r\n5\r\n body\r\n0\r\n\r\n",
+    );
+    let written = SimpleClient::new()
+        .request(Method::GET, url)
+        .send_streaming()
+        .unwrap()
+        .save_to(&path)
+        .unwrap();
+    assert_eq!(8, written);
+    assert_eq!(b"new body".to_vec(), std::fs::read(&path).unwrap());
+    assert_eq!(1, std::fs::read_dir(&dir).unwrap().count());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,191 @@
+#![allow(dead_code)]
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::str;
+use std::sync::mpsc;
+use std::thread;
+
+/// Serves `response` verbatim to the first connection and returns the URL to
+/// reach it. The request sent by the client is read and discarded.
+pub fn serve_once(response: &'static [u8]) -> String {
+    serve_capturing(response).0
+}
+
+/// Like `serve_once`, but also hands back the raw request (head and
+/// `Content-Length` or chunked body) the client sent.
+pub fn serve_capturing(response: &'static [u8]) -> (String, mpsc::Receiver<Vec<u8>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let request = read_request(&mut stream);
+        let _ = stream.write_all(response);
+        let _ = sender.send(request);
+    });
+    (format!("http://{}/", addr), receiver)
+}
+
+/// Serves `responses` to consecutive connections, one each, handing back
+/// every request in order.
+pub fn serve_sequence(responses: Vec<Vec<u8>>) -> (String, mpsc::Receiver<Vec<u8>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for response in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let request = read_request(&mut stream);
+            let _ = stream.write_all(&response);
+            let _ = sender.send(request);
+        }
+    });
+    (format!("http://{}/", addr), receiver)
+}
+
+/// Serves `response` to every connection until the test process exits.
+pub fn serve_forever(response: &'static [u8]) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(_) => continue,
+            };
+            thread::spawn(move || {
+                read_request(&mut stream);
+                let _ = stream.write_all(response);
+            });
+        }
+    });
+    format!("http://{}/", addr)
+}
+
+fn read_request<R: Read>(stream: &mut R) -> Vec<u8> {
+    let mut request = Vec::new();
+    let mut buffer = [0; 1024];
+    let head_end = loop {
+        if let Some(end) = head_end(&request) {
+            break end;
+        }
+        match stream.read(&mut buffer) {
+            Ok(0) | Err(_) => return request,
+            Ok(n) => request.extend_from_slice(&buffer[..n]),
+        }
+    };
+    let head = str::from_utf8(&request[..head_end])
+        .unwrap_or("")
+        .to_string();
+    if head
+        .to_ascii_lowercase()
+        .contains("\r\ntransfer-encoding: chunked\r\n")
+    {
+        while !chunked_body_complete(&request[head_end..]) {
+            match stream.read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => request.extend_from_slice(&buffer[..n]),
+            }
+        }
+        return request;
+    }
+    let content_length = head
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(2, ':');
+            match (parts.next(), parts.next()) {
+                (Some(name), Some(value)) if name.eq_ignore_ascii_case("content-length") => {
+                    value.trim().parse::<usize>().ok()
+                }
+                _ => None,
+            }
+        })
+        .next()
+        .unwrap_or(0);
+    while request.len() < head_end + content_length {
+        match stream.read(&mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => request.extend_from_slice(&buffer[..n]),
+        }
+    }
+    request
+}
+
+// Whether `body` holds a whole chunked body, up to the blank line after any
+// trailers.
+fn chunked_body_complete(mut body: &[u8]) -> bool {
+    loop {
+        let line_end = match body.windows(2).position(|window| window == b"\r\n") {
+            Some(end) => end,
+            None => return false,
+        };
+        let size = str::from_utf8(&body[..line_end]).ok().and_then(|line| {
+            usize::from_str_radix(line.split(';').next().unwrap().trim(), 16).ok()
+        });
+        match size {
+            Some(0) => {
+                let trailers = &body[line_end + 2..];
+                return trailers.starts_with(b"\r\n")
+                    || trailers.windows(4).any(|window| window == b"\r\n\r\n");
+            }
+            Some(size) if body.len() >= line_end + 2 + size + 2 => {
+                body = &body[line_end + 2 + size + 2..];
+            }
+            _ => return false,
+        }
+    }
+}
+
+fn head_end(request: &[u8]) -> Option<usize> {
+    let crlf = request
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .map(|i| i + 4);
+    let lf = request
+        .windows(2)
+        .position(|window| window == b"\n\n")
+        .map(|i| i + 2);
+    match (crlf, lf) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Writes `head` to the first connection, then trickles out `body` one byte
+/// every `interval`.
+pub fn serve_dripping(
+    head: &'static [u8],
+    body: &'static [u8],
+    interval: ::std::time::Duration,
+) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        read_request(&mut stream);
+        if stream.write_all(head).is_err() {
+            return;
+        }
+        for byte in body {
+            thread::sleep(interval);
+            if stream.write_all(&[*byte]).is_err() {
+                return;
+            }
+        }
+    });
+    format!("http://{}/", addr)
+}
+
+/// Accepts one connection and never answers, keeping the socket open for
+/// `hold` so the client has to give up on its own.
+pub fn serve_silent(hold: ::std::time::Duration) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        thread::sleep(hold);
+        drop(stream);
+    });
+    format!("http://{}/", addr)
+}
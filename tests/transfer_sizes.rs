@@ -0,0 +1,15 @@
+extern crate glass_fi;
+
+mod support;
+
+use glass_fi::SimpleClient;
+
+#[test]
+fn transfer_sizes() {
+    let url = support::serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello");
+    let sizes = SimpleClient::new().get(url).unwrap().transfer_sizes();
+    assert_eq!(38, sizes.header_bytes_received);
+    assert_eq!(5, sizes.body_bytes_received);
+    assert_eq!(0, sizes.body_bytes_sent);
+    assert!(sizes.header_bytes_sent > 0);
+}
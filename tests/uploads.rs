@@ -0,0 +1,212 @@
+extern crate bytes;
+extern crate glass_fi;
+extern crate tokio;
+
+mod support;
+
+use std::env;
+use std::fs;
+use std::io::{self, Cursor};
+use std::thread;
+
+use bytes::Bytes;
+use glass_fi::{Body, HeaderMap, HttpResponseError, Method, RedirectPolicy, SimpleClient};
+use tokio::prelude::*;
+use tokio::sync::mpsc;
+
+fn body_of(request: &[u8]) -> &[u8] {
+    let end = request
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .unwrap();
+    &request[end + 4..]
+}
+
+#[test]
+fn stream_body_is_sent_chunked() {
+    let (url, request) = support::serve_capturing(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let pieces = vec![
+        Bytes::from(&b"hello"[..]),
+        Bytes::new(),
+        Bytes::from(&b", world"[..]),
+    ];
+    let response = SimpleClient::new()
+        .request(Method::PUT, url)
+        .header("Content-Length", "99")
+        .body(Body::from_stream(stream::iter_ok(pieces)))
+        .send()
+        .unwrap();
+    assert_eq!(204, response.status.as_u16());
+
+    let request = request.recv().unwrap();
+    let head = String::from_utf8_lossy(&request);
+    assert!(
+        head.contains("\r\nTransfer-Encoding: chunked\r\n"),
+        "{}",
+        head
+    );
+    assert!(!head.contains("Content-Length"), "{}", head);
+    assert_eq!(
+        &b"5\r\nhello\r\n7\r\n, world\r\n0\r\n\r\n"[..],
+        body_of(&request)
+    );
+    assert_eq!(
+        body_of(&request).len() as u64,
+        response.transfer_sizes().body_bytes_sent
+    );
+}
+
+#[test]
+fn reader_body_is_sent_chunked() {
+    let (url, request) = support::serve_capturing(b"HTTP/1.1 204 No Content\r\n\r\n");
+    SimpleClient::new()
+        .post(url, Body::from_reader(Cursor::new(b"abc".to_vec())))
+        .unwrap();
+    assert_eq!(
+        &b"3\r\nabc\r\n0\r\n\r\n"[..],
+        body_of(&request.recv().unwrap())
+    );
+}
+
+#[test]
+fn file_body_is_sent_with_its_length() {
+    let path = env::temp_dir().join(format!("glass-fi-upload-{}", std::process::id()));
+    fs::write(&path, b"file contents").unwrap();
+    let (url, request) = support::serve_capturing(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let body = Body::from_file(&path).unwrap();
+    SimpleClient::new().post(url, body).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let request = request.recv().unwrap();
+    let head = String::from_utf8_lossy(&request);
+    assert!(head.contains("\r\nContent-Length: 13\r\n"), "{}", head);
+    assert!(!head.contains("Transfer-Encoding"), "{}", head);
+    assert_eq!(&b"file contents"[..], body_of(&request));
+
+    match Body::from_file(&path) {
+        Err(ref err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn stream_errors_fail_the_request() {
+    let url = support::serve_forever(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let pieces: Vec<Result<Bytes, std::io::Error>> = vec![
+        Ok(Bytes::from(&b"partial"[..])),
+        Err(std::io::Error::other("source failed")),
+    ];
+    match SimpleClient::new().post(url, Body::from_stream(stream::iter_result(pieces))) {
+        Err(HttpResponseError::Io(ref err)) => assert_eq!("source failed", err.to_string()),
+        other => panic!("unexpected result: {:?}", other.map(|r| r.status)),
+    }
+}
+
+#[test]
+fn streamed_body_is_not_replayed_on_redirect() {
+    let (url, request) = support::serve_capturing(
+        b"HTTP/1.1 307 Temporary Redirect\r\nLocation: /again\r\nContent-Length: 0\r\n\r\n",
+    );
+    let client = SimpleClient::builder()
+        .redirect(RedirectPolicy::Limited(5))
+        .build()
+        .unwrap();
+    let response = client
+        .post(url, Body::from_reader(Cursor::new(b"once".to_vec())))
+        .unwrap();
+    assert_eq!(307, response.status.as_u16());
+    assert_eq!(
+        &b"4\r\nonce\r\n0\r\n\r\n"[..],
+        body_of(&request.recv().unwrap())
+    );
+}
+
+#[test]
+fn channel_body_is_sent_with_computed_trailers() {
+    let (url, request) = support::serve_capturing(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let (mut pieces, receiver) = mpsc::channel::<Bytes>(1);
+    let (trailers, computed) = mpsc::channel::<HeaderMap>(1);
+    let producer = thread::spawn(move || {
+        let mut checksum = 0u32;
+        for piece in &["abc", "defg"] {
+            checksum = piece.bytes().fold(checksum, |sum, b| sum + u32::from(b));
+            pieces = pieces.send(Bytes::from(piece.as_bytes())).wait().unwrap();
+        }
+        drop(pieces);
+        let mut fields = HeaderMap::new();
+        fields.append("X-Checksum", checksum.to_string());
+        trailers.send(fields).wait().unwrap();
+    });
+    let body = Body::from_stream(receiver.map_err(io::Error::other)).with_trailers(
+        computed
+            .into_future()
+            .map(|(fields, _)| fields.unwrap())
+            .map_err(|(err, _)| io::Error::other(err)),
+    );
+    SimpleClient::new()
+        .request(Method::POST, url)
+        .header("Trailer", "X-Checksum")
+        .body(body)
+        .send()
+        .unwrap();
+    producer.join().unwrap();
+
+    let request = request.recv().unwrap();
+    assert_eq!(
+        &b"3\r\nabc\r\n4\r\ndefg\r\n0\r\nX-Checksum: 700\r\n\r\n"[..],
+        body_of(&request)
+    );
+}
+
+#[test]
+fn trailers_make_sized_bodies_chunked() {
+    let (url, request) = support::serve_capturing(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let body = Body::from("hello").with_trailers(future::ok(HeaderMap::new()));
+    SimpleClient::new()
+        .request(Method::PUT, url)
+        .body(body)
+        .send()
+        .unwrap();
+
+    let request = request.recv().unwrap();
+    let head = String::from_utf8_lossy(&request);
+    assert!(
+        head.contains("\r\nTransfer-Encoding: chunked\r\n"),
+        "{}",
+        head
+    );
+    assert!(!head.contains("Content-Length"), "{}", head);
+    assert_eq!(&b"5\r\nhello\r\n0\r\n\r\n"[..], body_of(&request));
+}
+
+#[test]
+fn sized_stream_body_has_content_length() {
+    let (url, request) = support::serve_capturing(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let pieces = vec![Bytes::from(&b"ab"[..]), Bytes::from(&b"cd"[..])];
+    SimpleClient::new()
+        .request(Method::PUT, url)
+        .body(Body::from_sized_stream(stream::iter_ok(pieces), 4))
+        .send()
+        .unwrap();
+
+    let request = request.recv().unwrap();
+    let head = String::from_utf8_lossy(&request);
+    assert!(head.contains("\r\nContent-Length: 4\r\n"), "{}", head);
+    assert_eq!(&b"abcd"[..], body_of(&request));
+}
+
+#[test]
+fn framing_trailers_are_rejected() {
+    let (url, _request) = support::serve_capturing(b"HTTP/1.1 204 No Content\r\n\r\n");
+    let mut trailers = HeaderMap::new();
+    trailers.append("content-length", "5");
+    let body = Body::from("hello").with_trailers(future::ok(trailers));
+    match SimpleClient::new()
+        .request(Method::PUT, url)
+        .body(body)
+        .send()
+    {
+        Err(HttpResponseError::InvalidHeader(_)) => {}
+        other => panic!("unexpected result: {:?}", other.map(|r| r.status)),
+    }
+}